@@ -0,0 +1,42 @@
+// Exemple d'intégration du widget de dessin dans une autre application egui : une zone
+// d'annotation sous un texte, avec la barre d'outils qu'on peut masquer.
+//
+//     cargo run --example annotate --features widget
+
+use eframe::egui;
+use rust_paint::canvas::PaintCanvas;
+
+struct Annotate {
+    canvas: PaintCanvas, // Gardé d'une image à l'autre : geste en cours et texture
+    toolbar: bool,
+}
+
+impl eframe::App for Annotate {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Notes de réunion");
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.toolbar, "Barre d'outils").changed() {
+                    self.canvas.set_toolbar(self.toolbar);
+                }
+                ui.label(format!("{} annotations", self.canvas.document().len()));
+            });
+            egui::Frame::canvas(ui.style()).show(ui, |ui| {
+                ui.add(&mut self.canvas);
+            });
+        });
+    }
+}
+
+fn main() -> eframe::Result<()> {
+    eframe::run_native(
+        "Annotations",
+        eframe::NativeOptions::default(),
+        Box::new(|_| {
+            Box::new(Annotate {
+                canvas: PaintCanvas::new().with_background(egui::Color32::from_gray(250)),
+                toolbar: true,
+            })
+        }),
+    )
+}
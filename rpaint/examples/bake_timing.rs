@@ -0,0 +1,246 @@
+// Temps de « cuisson » du canevas par image affichée, sur des documents de la taille de
+// ceux du générateur de traits de test (section ⚙ Performances). Compare `bake_canvas`
+// avant et après la passe de réduction des allocations :
+// - avant, la zone de la texture était arrondie au pixel : dès que le panneau du canevas
+//   ne tombait pas sur un pixel entier, elle ne lui était jamais égale et l'image était
+//   entièrement refaite, dans une nouvelle allocation, puis copiée pour l'envoi ;
+// - après, la zone du panneau est gardée telle quelle, et l'image, partagée avec egui par
+//   un `Arc`, est réutilisée même quand elle doit être refaite (annulation).
+//
+//     cargo run --release --example bake_timing [-- 1000 10000]
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use epaint::{Color32, ColorImage, Pos2, Rect, TessellationOptions, Tessellator, pos2, vec2};
+use rust_paint::document::{Document, Line};
+use rust_paint::raster::{self, Blend};
+
+// Panneau du canevas, à droite d'un panneau de réglages de largeur non entière, à un pixel
+// par point et par unité du dessin
+const CANVAS: Rect = Rect {
+    min: pos2(200.5, 24.0),
+    max: pos2(1800.0, 924.0),
+};
+const FRAMES: u32 = 60;
+
+// Trait aléatoire comme ceux de `generate_stress_strokes`
+fn random_stroke(rng: &mut fastrand::Rng) -> Line {
+    let area = Rect::from_min_size(CANVAS.min, CANVAS.size());
+    let mut point = Pos2::new(
+        area.min.x + rng.f32() * area.width(),
+        area.min.y + rng.f32() * area.height(),
+    );
+    let mut points = Vec::with_capacity(32);
+    for _ in 0..rng.usize(2..32) {
+        points.push(point);
+        point = area.clamp(point + vec2(rng.f32() - 0.5, rng.f32() - 0.5) * 40.0);
+    }
+    let color = Color32::from_rgb(rng.u8(..), rng.u8(..), rng.u8(..));
+    Line::new(points, color, 1.0 + rng.f32() * 15.0, 1.0)
+}
+
+// Document de `count` traits, toujours les mêmes
+fn document(count: usize) -> (Document, fastrand::Rng) {
+    let mut rng = fastrand::Rng::with_seed(1395);
+    let mut document = Document::new();
+    for _ in 0..count {
+        document.add_stroke(random_stroke(&mut rng));
+    }
+    (document, rng)
+}
+
+// Taille en pixels de l'image couvrant `rect`
+fn pixel_size(rect: Rect) -> [usize; 2] {
+    [
+        rect.width().round() as usize,
+        rect.height().round() as usize,
+    ]
+}
+
+// Dessine dans `image`, dont le coin est en `origin`, les objets à partir de `from`, avec
+// les maillages en cache comme l'application, et retourne la zone modifiée
+fn bake(
+    document: &mut Document,
+    from: usize,
+    image: &mut ColorImage,
+    origin: Pos2,
+    tessellator: &mut Tessellator,
+) -> Rect {
+    let mut dirty = Rect::NOTHING;
+    for object in &mut document.objects_mut()[from..] {
+        for line in object.lines_mut() {
+            if line.points().len() < 2 {
+                continue;
+            }
+            let blend = if line.is_eraser() {
+                Blend::Erase
+            } else {
+                Blend::Over
+            };
+            let mesh = line.mesh(tessellator);
+            if let Some(region) = raster::draw_mesh(image, mesh, origin, 1.0, blend) {
+                dirty = dirty.union(region);
+            }
+        }
+    }
+    dirty
+}
+
+// Image affichée par le canevas ; l'envoi à egui est remplacé par `black_box`
+trait Canvas {
+    fn new() -> Self;
+    fn frame(&mut self, document: &mut Document, rect: Rect, tessellator: &mut Tessellator);
+}
+
+// `bake_canvas` avant la passe : zone arrondie, nouvelle image et copie à chaque cuisson
+// complète
+struct Before {
+    image: ColorImage,
+    canvas_rect: Rect,
+    baked_lines: usize,
+    uploaded: bool,
+}
+
+impl Canvas for Before {
+    fn new() -> Self {
+        Self {
+            image: ColorImage::default(),
+            canvas_rect: Rect::NOTHING,
+            baked_lines: 0,
+            uploaded: false,
+        }
+    }
+
+    fn frame(&mut self, document: &mut Document, rect: Rect, tessellator: &mut Tessellator) {
+        let size = pixel_size(rect);
+        let full_rebake =
+            !self.uploaded || rect != self.canvas_rect || self.baked_lines > document.len();
+        if full_rebake {
+            self.image = ColorImage::new(size, Color32::TRANSPARENT);
+            self.canvas_rect = Rect::from_min_size(rect.min, vec2(size[0] as f32, size[1] as f32));
+            self.baked_lines = 0;
+        } else if self.baked_lines == document.len() {
+            return;
+        }
+        let dirty = bake(
+            document,
+            self.baked_lines,
+            &mut self.image,
+            rect.min,
+            tessellator,
+        );
+        self.baked_lines = document.len();
+        if full_rebake {
+            black_box(self.image.clone());
+            self.uploaded = true;
+        } else if dirty.is_positive() {
+            black_box(self.image.region(&dirty, None));
+        }
+    }
+}
+
+// `bake_canvas` après la passe : zone du panneau telle quelle, image partagée et réutilisée
+struct After {
+    image: Arc<ColorImage>,
+    canvas_rect: Rect,
+    baked_lines: usize,
+    uploaded: bool,
+}
+
+impl Canvas for After {
+    fn new() -> Self {
+        Self {
+            image: Arc::default(),
+            canvas_rect: Rect::NOTHING,
+            baked_lines: 0,
+            uploaded: false,
+        }
+    }
+
+    fn frame(&mut self, document: &mut Document, rect: Rect, tessellator: &mut Tessellator) {
+        let size = pixel_size(rect);
+        let full_rebake =
+            !self.uploaded || rect != self.canvas_rect || self.baked_lines > document.len();
+        if !full_rebake && self.baked_lines == document.len() {
+            return;
+        }
+        // egui libère sa référence une fois la texture envoyée : pas de copie ici
+        let image = Arc::make_mut(&mut self.image);
+        if full_rebake {
+            if image.size == size {
+                image.pixels.fill(Color32::TRANSPARENT);
+            } else {
+                *image = ColorImage::new(size, Color32::TRANSPARENT);
+            }
+            self.canvas_rect = rect;
+            self.baked_lines = 0;
+        }
+        let dirty = bake(document, self.baked_lines, image, rect.min, tessellator);
+        self.baked_lines = document.len();
+        if full_rebake {
+            black_box(Arc::clone(&self.image));
+            self.uploaded = true;
+        } else if dirty.is_positive() {
+            black_box(image.region(&dirty, None));
+        }
+    }
+}
+
+// Ce qui arrive au dessin avant chaque image affichée
+#[derive(Clone, Copy)]
+enum Scenario {
+    Idle,   // Rien : le dessin est seulement affiché
+    Stroke, // Un trait ajouté
+    Undo,   // Une annulation
+}
+
+// Durée moyenne d'une image de `scenario` sur un document de `count` traits
+fn time<C: Canvas>(count: usize, scenario: Scenario) -> Duration {
+    let (mut document, mut rng) = document(count + FRAMES as usize);
+    let mut tessellator = Tessellator::new(1.0, TessellationOptions::default(), [1, 1], Vec::new());
+    // Première cuisson : les maillages sont ensuite en cache, comme dans l'application
+    let mut canvas = C::new();
+    canvas.frame(&mut document, CANVAS, &mut tessellator);
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        match scenario {
+            Scenario::Idle => {}
+            Scenario::Stroke => document.add_stroke(random_stroke(&mut rng)),
+            Scenario::Undo => {
+                document.undo();
+            }
+        }
+        canvas.frame(&mut document, CANVAS, &mut tessellator);
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    let counts: Vec<usize> = std::env::args()
+        .skip(1)
+        .map(|count| count.parse().expect("nombre de traits attendu"))
+        .collect();
+    let counts = if counts.is_empty() {
+        vec![1_000, 10_000]
+    } else {
+        counts
+    };
+    for count in counts {
+        println!("{count} traits, par image affichée :");
+        for (name, scenario) in [
+            ("sans modification", Scenario::Idle),
+            ("un trait ajouté", Scenario::Stroke),
+            ("une annulation", Scenario::Undo),
+        ] {
+            let before = time::<Before>(count, scenario);
+            let after = time::<After>(count, scenario);
+            println!(
+                "  {name:<18} avant {:>8.3} ms, après {:>8.3} ms",
+                before.as_secs_f64() * 1e3,
+                after.as_secs_f64() * 1e3,
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Exemple d'extension : un aérographe qui dépose des points autour du pointeur.
+//
+//     cargo build --example spray_plugin
+//
+// puis copier la bibliothèque produite dans `target/debug/examples` (libspray_plugin.so,
+// .dylib ou spray_plugin.dll) dans le dossier `plugins`, et lancer l'application avec
+// `cargo run --features plugins`.
+
+use epaint::{Pos2, vec2};
+use rust_paint::document::{Action, Document, DrawObject, Line};
+use rust_paint::tool::{Brush, PointerEvent, Tool};
+
+// Points ajoutés à chaque événement du pointeur
+const DOTS_PER_EVENT: usize = 8;
+
+// Angle d'or : les points successifs se répartissent régulièrement autour du centre
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+#[derive(Default)]
+struct Spray {
+    dots: Vec<Pos2>, // Points du geste en cours
+}
+
+impl Spray {
+    fn spray(&mut self, center: Pos2, radius: f32) {
+        for _ in 0..DOTS_PER_EVENT {
+            let i = self.dots.len() as f32;
+            let angle = i * GOLDEN_ANGLE;
+            let distance = radius * (i * 0.618).fract().sqrt();
+            self.dots
+                .push(center + distance * vec2(angle.cos(), angle.sin()));
+        }
+    }
+
+    fn lines(&self, brush: Brush) -> Vec<Line> {
+        let width = (brush.width / 4.0).max(1.0);
+        self.dots
+            .iter()
+            .map(|&dot| {
+                Line::new(
+                    vec![dot, dot + vec2(0.5, 0.0)],
+                    brush.color,
+                    width,
+                    brush.opacity,
+                )
+            })
+            .collect()
+    }
+}
+
+impl Tool for Spray {
+    fn name(&self) -> &'static str {
+        "Aérographe"
+    }
+
+    fn icon(&self) -> &'static str {
+        "💨"
+    }
+
+    fn pointer_event(&mut self, event: PointerEvent, brush: Brush, _: &Document) -> Vec<Action> {
+        match event {
+            PointerEvent::Press(pos) | PointerEvent::Move(pos) => {
+                self.spray(pos, brush.width * 3.0);
+                Vec::new()
+            }
+            PointerEvent::Release => {
+                let lines = self.lines(brush);
+                self.dots.clear();
+                if lines.is_empty() {
+                    return Vec::new();
+                }
+                // Tous les points du geste forment un seul objet, annulé d'un coup
+                let dots = lines.into_iter().map(DrawObject::from).collect();
+                vec![Action::Push(DrawObject::group(dots))]
+            }
+        }
+    }
+
+    fn preview(&self, brush: Brush) -> Vec<Line> {
+        self.lines(brush)
+    }
+
+    fn cancel(&mut self) {
+        self.dots.clear();
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn rpaint_register_tools(tools: &mut Vec<Box<dyn Tool>>) {
+    tools.push(Box::new(Spray::default()));
+}
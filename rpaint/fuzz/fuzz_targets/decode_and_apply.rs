@@ -0,0 +1,42 @@
+// Données hostiles ou corrompues : un document et des actions au format JSON enregistré,
+// décodés puis appliqués, annulés, rétablis, rendus et exportés. Aucun de ces chemins ne
+// doit paniquer, quel que soit le contenu.
+//
+// Depuis `rpaint` : `cargo +nightly fuzz run decode_and_apply -- -dict=fuzz/decode_and_apply.dict`.
+// Si `rustix` ne compile pas avec une nightly récente, ajouter `RUSTFLAGS="-Zallow-features="`.
+
+#![no_main]
+
+use epaint::Pos2;
+use libfuzzer_sys::fuzz_target;
+use rust_paint::document::{Action, Document};
+use rust_paint::raster;
+
+#[derive(serde::Deserialize)]
+struct Input {
+    document: Document,
+    actions: Vec<Action>,
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(Input {
+        mut document,
+        actions,
+    }) = serde_json::from_slice(data)
+    else {
+        return;
+    };
+    let count = actions.len();
+    for action in actions {
+        document.apply(action);
+    }
+    for _ in 0..count {
+        document.undo();
+    }
+    for _ in 0..count {
+        document.redo();
+    }
+    document.memory_size();
+    document.export_svg();
+    raster::render(&document, [32, 32], Pos2::ZERO, 1.0);
+});
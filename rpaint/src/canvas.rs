@@ -0,0 +1,329 @@
+// Zone de dessin autonome pour les applications egui : document, outils et rendu réunis
+// dans un widget, avec une barre d'outils facultative. Pensée pour ajouter des annotations
+// à une autre application ; Rust Paint garde sa propre zone de dessin, plus complète
+// (zoom, calques de référence, présentation).
+
+use egui::{
+    Color32, Pos2, Rect, Response, Sense, Shape, Stroke, TextureHandle, TextureOptions, Ui, Vec2,
+    Widget,
+};
+
+use crate::document::Document;
+use crate::raster;
+use crate::tool::{Brush, Freehand, Outline, PointerEvent, StraightLine, Tool};
+
+/// Zone de dessin à intégrer dans une interface egui.
+///
+/// Le widget possède son document : les objets sont en points, depuis le coin supérieur
+/// gauche de la zone. Il doit être gardé d'une image à l'autre, dans l'état de
+/// l'application, pour conserver le geste en cours et la texture du dessin.
+///
+/// ```no_run
+/// # fn ui(ui: &mut egui::Ui, canvas: &mut rust_paint::canvas::PaintCanvas) {
+/// let response = canvas.show(ui);
+/// if response.changed() {
+///     println!("{} objets", canvas.document().len());
+/// }
+/// # }
+/// ```
+pub struct PaintCanvas {
+    document: Document,
+    tools: Vec<Box<dyn Tool>>,
+    tool: usize, // Indice de l'outil actif dans `tools`
+    brush: Brush,
+    background: Color32,
+    toolbar: bool,
+    size: Option<Vec2>, // Taille demandée ; toute la place disponible sinon
+    drawing: bool,      // Un geste est en cours
+    texture: Option<TextureHandle>,
+    baked: Option<([usize; 2], f32)>, // Taille en pixels et échelle de la texture
+}
+
+impl Default for PaintCanvas {
+    fn default() -> Self {
+        Self {
+            document: Document::new(),
+            tools: vec![
+                Box::<Freehand>::default(),
+                Box::new(Freehand::eraser()),
+                Box::<StraightLine>::default(),
+                Box::new(Outline::rectangle()),
+                Box::new(Outline::ellipse()),
+            ],
+            tool: 0,
+            brush: Brush {
+                color: Color32::BLACK,
+                width: 3.0,
+                opacity: 1.0,
+            },
+            background: Color32::TRANSPARENT,
+            toolbar: true,
+            size: None,
+            drawing: false,
+            texture: None,
+            baked: None,
+        }
+    }
+}
+
+impl PaintCanvas {
+    /// Zone vide : outils intégrés (main levée, gomme, ligne, rectangle, ellipse),
+    /// trait noir de 3 points, fond transparent et barre d'outils affichée.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remplace le document, par exemple par des annotations enregistrées.
+    pub fn with_document(mut self, document: Document) -> Self {
+        self.set_document(document);
+        self
+    }
+
+    /// Remplace les outils proposés ; le premier devient l'outil actif.
+    ///
+    /// # Panics
+    ///
+    /// Si `tools` est vide.
+    pub fn with_tools(mut self, tools: Vec<Box<dyn Tool>>) -> Self {
+        assert!(!tools.is_empty(), "PaintCanvas sans outil");
+        self.tools = tools;
+        self.tool = 0;
+        self
+    }
+
+    /// Affiche ou masque la barre d'outils au-dessus de la zone. Sans elle, l'outil et
+    /// le pinceau se choisissent par [`select_tool`](Self::select_tool) et
+    /// [`set_brush`](Self::set_brush).
+    pub fn with_toolbar(mut self, toolbar: bool) -> Self {
+        self.toolbar = toolbar;
+        self
+    }
+
+    /// Couleur de fond de la zone, quand le document n'a pas de fond propre ; transparente
+    /// par défaut, pour annoter ce qui est dessous.
+    pub fn with_background(mut self, background: Color32) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Taille de la zone, en points, sans la barre d'outils ; par défaut toute la place
+    /// disponible.
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Accès au document pour le modifier directement (scripts, annulation…) ;
+    /// le rendu suit ses modifications.
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.document
+    }
+
+    /// Remplace le document ; le geste en cours est abandonné.
+    pub fn set_document(&mut self, document: Document) {
+        self.cancel();
+        self.document = document;
+        self.baked = None;
+    }
+
+    /// Affiche ou masque la barre d'outils, comme [`with_toolbar`](Self::with_toolbar).
+    pub fn set_toolbar(&mut self, toolbar: bool) {
+        self.toolbar = toolbar;
+    }
+
+    pub fn into_document(self) -> Document {
+        self.document
+    }
+
+    pub fn brush(&self) -> Brush {
+        self.brush
+    }
+
+    pub fn set_brush(&mut self, brush: Brush) {
+        self.brush = brush;
+    }
+
+    /// Indice de l'outil actif.
+    pub fn tool_index(&self) -> usize {
+        self.tool
+    }
+
+    /// Active l'outil d'indice `index` ; retourne `false` s'il n'existe pas.
+    pub fn select_tool(&mut self, index: usize) -> bool {
+        if index >= self.tools.len() {
+            return false;
+        }
+        if index != self.tool {
+            self.cancel();
+            self.tool = index;
+        }
+        true
+    }
+
+    /// Annule la dernière modification ; retourne `false` s'il n'y en a pas.
+    pub fn undo(&mut self) -> bool {
+        self.cancel();
+        self.document.undo()
+    }
+
+    /// Rétablit la dernière modification annulée ; retourne `false` s'il n'y en a pas.
+    pub fn redo(&mut self) -> bool {
+        self.cancel();
+        self.document.redo()
+    }
+
+    /// Affiche la barre d'outils, si elle est active, puis la zone de dessin.
+    /// La réponse est celle de la zone ; elle est marquée modifiée (`changed()`)
+    /// quand le document a changé pendant cette image.
+    pub fn show(&mut self, ui: &mut Ui) -> Response {
+        let mut changed = false;
+        if self.toolbar {
+            ui.horizontal_wrapped(|ui| changed |= self.toolbar_ui(ui));
+        }
+        let size = self.size.unwrap_or_else(|| ui.available_size());
+        let (mut response, painter) = ui.allocate_painter(size, Sense::drag());
+        let rect = response.rect;
+
+        // Le geste suit le pointeur tant que le bouton principal reste enfoncé
+        if let Some(pointer_pos) = response.interact_pointer_pos() {
+            if response.dragged_by(egui::PointerButton::Primary) {
+                let pos = Pos2::ZERO + (pointer_pos - rect.min);
+                let event = if self.drawing {
+                    PointerEvent::Move(pos)
+                } else {
+                    self.drawing = true;
+                    PointerEvent::Press(pos)
+                };
+                changed |= self.tool_event(event);
+            }
+        } else if self.drawing {
+            self.drawing = false;
+            changed |= self.tool_event(PointerEvent::Release);
+        }
+        if changed {
+            response.mark_changed();
+        }
+
+        if ui.is_rect_visible(rect) {
+            let background = self.document.properties().background;
+            painter.rect_filled(rect, 0.0, background.unwrap_or(self.background));
+            self.bake(ui.ctx(), rect);
+            if let Some(texture) = &self.texture {
+                let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                painter.image(texture.id(), rect, uv, Color32::WHITE);
+            }
+            // Aperçu du geste en cours ; la gomme montre la zone qu'elle va effacer
+            for line in self.tools[self.tool].preview(self.brush) {
+                let mut points: Vec<Pos2> = line
+                    .points()
+                    .iter()
+                    .map(|point| rect.min + point.to_vec2())
+                    .collect();
+                if line.is_closed() {
+                    points.extend(points.first().copied());
+                }
+                let color = if line.is_eraser() {
+                    ui.visuals().weak_text_color().gamma_multiply(0.5)
+                } else {
+                    line.color().gamma_multiply(line.opacity())
+                };
+                painter.add(Shape::line(points, Stroke::new(line.width(), color)));
+            }
+            if response.hovered() && !self.drawing {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+            }
+        }
+        response
+    }
+
+    // Outils, couleur, épaisseur et historique ; retourne `true` si le document a changé
+    fn toolbar_ui(&mut self, ui: &mut Ui) -> bool {
+        for index in 0..self.tools.len() {
+            let tool = &self.tools[index];
+            let (icon, name) = (tool.icon(), tool.name());
+            if ui
+                .selectable_label(index == self.tool, icon)
+                .on_hover_text(name)
+                .clicked()
+            {
+                self.select_tool(index);
+            }
+        }
+        ui.separator();
+        ui.color_edit_button_srgba(&mut self.brush.color)
+            .on_hover_text("Couleur");
+        ui.add(
+            egui::DragValue::new(&mut self.brush.width)
+                .clamp_range(0.5..=100.0)
+                .speed(0.2)
+                .suffix(" pt"),
+        )
+        .on_hover_text("Épaisseur");
+        ui.separator();
+        let mut changed = false;
+        let can_undo = self.document.can_undo();
+        if ui
+            .add_enabled(can_undo, egui::Button::new("↩"))
+            .on_hover_text("Annuler")
+            .clicked()
+        {
+            changed |= self.undo();
+        }
+        let can_redo = self.document.can_redo();
+        if ui
+            .add_enabled(can_redo, egui::Button::new("↪"))
+            .on_hover_text("Rétablir")
+            .clicked()
+        {
+            changed |= self.redo();
+        }
+        changed
+    }
+
+    // Transmet un événement à l'outil actif ; retourne `true` si le document a changé
+    fn tool_event(&mut self, event: PointerEvent) -> bool {
+        let actions = self.tools[self.tool].pointer_event(event, self.brush, &self.document);
+        let mut applied = false;
+        for action in actions {
+            applied |= self.document.apply(action);
+        }
+        applied
+    }
+
+    fn cancel(&mut self) {
+        self.drawing = false;
+        self.tools[self.tool].cancel();
+    }
+
+    // Refait la texture du dessin si le document, la taille ou l'échelle ont changé
+    fn bake(&mut self, ctx: &egui::Context, rect: Rect) {
+        let pixels_per_point = ctx.pixels_per_point();
+        let size = [
+            (rect.width() * pixels_per_point).round() as usize,
+            (rect.height() * pixels_per_point).round() as usize,
+        ];
+        let changed = self.document.take_changed_from().is_some();
+        if !changed && self.texture.is_some() && self.baked == Some((size, pixels_per_point)) {
+            return;
+        }
+        let image = raster::render(&self.document, size, Pos2::ZERO, pixels_per_point);
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => {
+                self.texture =
+                    Some(ctx.load_texture("paint_canvas", image, TextureOptions::NEAREST))
+            }
+        }
+        self.baked = Some((size, pixels_per_point));
+    }
+}
+
+impl Widget for &mut PaintCanvas {
+    fn ui(self, ui: &mut Ui) -> Response {
+        self.show(ui)
+    }
+}
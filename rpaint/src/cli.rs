@@ -0,0 +1,44 @@
+// Arguments de la ligne de commande : document à ouvrir et réglages de démarrage,
+// utiles pour les bornes et les démonstrations scriptées.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::i18n::Language;
+
+/// Rust Paint : dessin à main levée, formes et animation image par image.
+#[derive(Parser, Default)]
+#[command(version)]
+pub struct Args {
+    /// Document à ouvrir (fichier .rpaint)
+    pub file: Option<PathBuf>,
+
+    /// Taille intérieure de la fenêtre, en points : LARGEURxHAUTEUR
+    #[arg(long, value_name = "LxH", value_parser = parse_window_size)]
+    pub window_size: Option<[f32; 2]>,
+
+    /// Langue de l'interface (fr, en), retenue dans les préférences
+    #[arg(long, value_name = "LANGUE", value_parser = parse_language)]
+    pub lang: Option<Language>,
+}
+
+// « 1280x720 » : deux dimensions strictement positives séparées par un x
+fn parse_window_size(text: &str) -> Result<[f32; 2], String> {
+    let (width, height) = text
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("taille attendue sous la forme LxH, « {text} » reçu"))?;
+    let dimension = |text: &str| match text.trim().parse::<f32>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        _ => Err(format!("dimension invalide : « {text} »")),
+    };
+    Ok([dimension(width)?, dimension(height)?])
+}
+
+fn parse_language(text: &str) -> Result<Language, String> {
+    match text.to_lowercase().as_str() {
+        "fr" | "français" | "francais" | "french" => Ok(Language::French),
+        "en" | "english" | "anglais" => Ok(Language::English),
+        _ => Err(format!("langue inconnue : « {text} » (fr ou en)")),
+    }
+}
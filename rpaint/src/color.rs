@@ -0,0 +1,71 @@
+// Conversions entre couleurs et texte hexadécimal.
+
+use epaint::Color32;
+
+/// Lit une couleur « #RRGGBB » ou « #RRGGBBAA » (le # est facultatif).
+pub fn parse_hex_color(text: &str) -> Option<Color32> {
+    let hex = text.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color32::from_rgba_unmultiplied(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+/// Écrit une couleur en hexadécimal, avec l'alpha seulement s'il n'est pas opaque.
+pub fn color_to_hex(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{r:02X}{g:02X}{b:02X}")
+    } else {
+        format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+    }
+}
+
+/// Sérialisation compacte d'une couleur, sous la forme hexadécimale de `color_to_hex`,
+/// à utiliser avec `#[serde(with = "crate::color::hex")]`. Exacte pour les couleurs
+/// opaques, celles de tous les traits : leur transparence est portée par leur opacité.
+pub mod hex {
+    use epaint::Color32;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(color: &Color32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::color_to_hex(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_hex_color(&text)
+            .ok_or_else(|| D::Error::custom(format!("couleur invalide : {text}")))
+    }
+}
+
+/// Comme [`hex`], pour une couleur facultative : `None` s'écrit `null`.
+pub mod hex_option {
+    use epaint::Color32;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => super::hex::serialize(color, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color32>, D::Error> {
+        #[derive(Deserialize)]
+        struct Hex(#[serde(with = "super::hex")] Color32);
+        Ok(Option::<Hex>::deserialize(deserializer)?.map(|Hex(color)| color))
+    }
+}
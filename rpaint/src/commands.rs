@@ -0,0 +1,365 @@
+// Registre des commandes : toutes les opérations que l'utilisateur peut déclencher, avec leur
+// raccourci et la condition qui les rend disponibles. Boutons, menus, raccourcis et scripts
+// passent tous par `PaintApp::run_command`, donc par le même code.
+
+use std::collections::BTreeMap;
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+use crate::export::Format;
+use crate::i18n::tr;
+use crate::{BrushMode, PaintApp};
+
+pub struct Command {
+    pub id: &'static str,
+    pub label: &'static str, // Icône puis nom, en français : traduit à l'affichage
+    // Remplaçable dans les préférences ; sans Ctrl, ignoré pendant la saisie de texte
+    pub default_shortcut: Option<KeyboardShortcut>,
+    pub enabled: fn(&PaintApp) -> bool,
+    pub run: fn(&mut PaintApp, &egui::Context),
+}
+
+const fn key(key: Key) -> Option<KeyboardShortcut> {
+    Some(KeyboardShortcut::new(Modifiers::NONE, key))
+}
+
+const fn ctrl(key: Key) -> Option<KeyboardShortcut> {
+    Some(KeyboardShortcut::new(Modifiers::COMMAND, key))
+}
+
+fn always(_: &PaintApp) -> bool {
+    true
+}
+
+pub const COMMANDS: &[Command] = &[
+    // Édition
+    Command {
+        id: "edit.undo",
+        label: "↩ Annuler",
+        default_shortcut: ctrl(Key::Z),
+        enabled: |app| app.document.can_undo(),
+        run: |app, _| app.undo(),
+    },
+    Command {
+        id: "edit.redo",
+        label: "↪ Rétablir",
+        default_shortcut: ctrl(Key::Y),
+        enabled: |app| app.document.can_redo(),
+        run: |app, _| app.redo(),
+    },
+    Command {
+        id: "edit.clear",
+        label: "🗑 Effacer tout",
+        default_shortcut: None,
+        enabled: |app| !app.document.is_empty(),
+        run: |app, _| app.request_clear(),
+    },
+    Command {
+        id: "edit.clear_redo",
+        label: "🗑 Vider le rétablissement",
+        default_shortcut: None,
+        enabled: |app| app.document.can_redo(),
+        run: |app, _| app.document.clear_redo(),
+    },
+    Command {
+        id: "edit.clear_history",
+        label: "🗑 Vider l'historique",
+        default_shortcut: None,
+        enabled: |app| {
+            app.all_frames()
+                .iter()
+                .any(|frame| frame.can_undo() || frame.can_redo())
+        },
+        run: |app, _| app.clear_history(),
+    },
+    // Objet sous le clic droit (menu contextuel du canevas)
+    Command {
+        id: "object.copy",
+        label: "🗐 Copier",
+        default_shortcut: None,
+        enabled: |app| app.context_object().is_some(),
+        run: |app, _| app.copy_context_object(),
+    },
+    Command {
+        id: "object.paste_here",
+        label: "📋 Coller ici",
+        default_shortcut: None,
+        enabled: |app| app.clipboard.is_some() && app.context_target.is_some(),
+        run: |app, _| app.paste_here(),
+    },
+    Command {
+        id: "object.bring_to_front",
+        label: "⬆ Premier plan",
+        default_shortcut: None,
+        enabled: |app| {
+            app.unlocked_context_object()
+                .is_some_and(|index| index + 1 < app.document.len())
+        },
+        run: |app, _| app.bring_to_front(),
+    },
+    Command {
+        id: "object.apply_color",
+        label: "🎨 Appliquer la couleur du pinceau",
+        default_shortcut: None,
+        enabled: |app| {
+            app.unlocked_context_object()
+                .is_some_and(|index| !app.document.objects()[index].is_eraser())
+        },
+        run: |app, _| app.apply_brush_color(),
+    },
+    Command {
+        id: "object.lock",
+        label: "🔒 Verrouiller",
+        default_shortcut: None,
+        enabled: |app| app.unlocked_context_object().is_some(),
+        run: |app, _| app.lock_context_object(true),
+    },
+    Command {
+        id: "object.unlock",
+        label: "🔓 Déverrouiller",
+        default_shortcut: None,
+        enabled: |app| app.context_object().is_some() && app.unlocked_context_object().is_none(),
+        run: |app, _| app.lock_context_object(false),
+    },
+    Command {
+        id: "object.delete",
+        label: "🗑 Supprimer l'objet",
+        default_shortcut: None,
+        enabled: |app| app.unlocked_context_object().is_some(),
+        run: |app, _| app.delete_context_object(),
+    },
+    // Fichier
+    Command {
+        id: "file.save",
+        label: "💾 Enregistrer",
+        default_shortcut: ctrl(Key::S),
+        enabled: always,
+        run: |app, _| {
+            app.save_file();
+        },
+    },
+    Command {
+        id: "file.export_png",
+        label: "📤 Exporter en PNG",
+        default_shortcut: None,
+        enabled: |app| !app.document.is_empty(),
+        run: |app, _| app.export(Format::Png),
+    },
+    Command {
+        id: "file.export_svg",
+        label: "📤 Exporter en SVG",
+        default_shortcut: None,
+        enabled: |app| !app.document.is_empty(),
+        run: |app, _| app.export(Format::Svg),
+    },
+    Command {
+        id: "file.export_frames",
+        label: "🎞 Exporter l'animation en PNG",
+        default_shortcut: None,
+        enabled: |app| app.all_frames().iter().any(|frame| !frame.is_empty()),
+        run: |app, _| app.export_frames(),
+    },
+    Command {
+        id: "file.properties",
+        label: "📝 Propriétés du document…",
+        default_shortcut: None,
+        enabled: always,
+        run: |app, _| app.properties_edit = Some(app.document.properties().clone()),
+    },
+    // Outils. S reste libre : la sélection du document n'a pas encore d'outil
+    Command {
+        id: "tool.freehand",
+        label: "✏ Main levée",
+        default_shortcut: key(Key::B),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::Freehand,
+    },
+    Command {
+        id: "tool.line",
+        label: "📏 Ligne",
+        default_shortcut: key(Key::L),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::StraightLine,
+    },
+    Command {
+        id: "tool.rectangle",
+        label: "⬜ Rectangle",
+        default_shortcut: key(Key::U),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::Rectangle,
+    },
+    Command {
+        id: "tool.ellipse",
+        label: "⭕ Ellipse",
+        default_shortcut: key(Key::O),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::Ellipse,
+    },
+    Command {
+        id: "tool.eraser",
+        label: "🧽 Gomme",
+        default_shortcut: key(Key::E),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::Eraser,
+    },
+    Command {
+        id: "tool.laser",
+        label: "🔦 Pointeur laser",
+        default_shortcut: key(Key::P),
+        enabled: always,
+        run: |app, _| app.mode = BrushMode::Laser,
+    },
+    // Affichage
+    Command {
+        id: "view.zoom_in",
+        label: "+ Zoom avant",
+        default_shortcut: None,
+        enabled: |app| app.zoom < *crate::ZOOM_RANGE.end(),
+        run: |app, _| app.zoom_at(app.zoom * 1.25, app.canvas_rect.center()),
+    },
+    Command {
+        id: "view.zoom_out",
+        label: "− Zoom arrière",
+        default_shortcut: None,
+        enabled: |app| app.zoom > *crate::ZOOM_RANGE.start(),
+        run: |app, _| app.zoom_at(app.zoom / 1.25, app.canvas_rect.center()),
+    },
+    Command {
+        id: "view.focus",
+        label: "⛶ Plein écran sans panneaux",
+        default_shortcut: key(Key::F11),
+        enabled: always,
+        run: |app, ctx| app.set_focus_mode(ctx, !app.focus_mode),
+    },
+    Command {
+        id: "view.presentation",
+        label: "📽 Présenter le dessin étape par étape",
+        default_shortcut: key(Key::F5),
+        enabled: always,
+        run: |app, ctx| app.set_presentation(ctx, app.presentation.is_none()),
+    },
+    Command {
+        id: "view.reference",
+        label: "🖼 Afficher l'image de référence",
+        default_shortcut: key(Key::R),
+        enabled: |app| app.reference.is_some(),
+        run: |app, _| {
+            if let Some(reference) = &mut app.reference {
+                reference.visible = !reference.visible;
+            }
+        },
+    },
+    Command {
+        id: "view.profiler",
+        label: "⏱ Profileur",
+        default_shortcut: key(Key::F3),
+        enabled: always,
+        run: |app, _| app.show_profiler = !app.show_profiler,
+    },
+    Command {
+        id: "view.log",
+        label: "📋 Journal",
+        default_shortcut: key(Key::F12),
+        enabled: always,
+        run: |app, _| app.show_log = !app.show_log,
+    },
+    // Animation ; en présentation, Page ↑ / Page ↓ changent d'étape et non d'image
+    Command {
+        id: "frame.previous",
+        label: "◀ Image précédente",
+        default_shortcut: key(Key::PageUp),
+        enabled: |app| app.presentation.is_none(),
+        run: |app, _| app.step_frame(false),
+    },
+    Command {
+        id: "frame.next",
+        label: "▶ Image suivante",
+        default_shortcut: key(Key::PageDown),
+        enabled: |app| app.presentation.is_none(),
+        run: |app, _| app.step_frame(true),
+    },
+    Command {
+        id: "frame.new",
+        label: "➕ Nouvelle image",
+        default_shortcut: None,
+        enabled: always,
+        run: |app, _| app.insert_frame(false),
+    },
+    Command {
+        id: "frame.duplicate",
+        label: "⎘ Dupliquer l'image",
+        default_shortcut: None,
+        enabled: always,
+        run: |app, _| app.insert_frame(true),
+    },
+    Command {
+        id: "frame.delete",
+        label: "🗑 Supprimer l'image",
+        default_shortcut: None,
+        enabled: |app| app.frames.len() > 1,
+        run: |app, _| app.delete_frame(),
+    },
+    Command {
+        id: "frame.play",
+        label: "⏵ Lecture / pause",
+        default_shortcut: None,
+        enabled: always,
+        run: |app, _| app.playing = !app.playing,
+    },
+    // Script
+    Command {
+        id: "script.run",
+        label: "▶ Exécuter",
+        default_shortcut: None,
+        enabled: |app| !app.script_source.trim().is_empty(),
+        run: |app, ctx| app.run_script(ctx),
+    },
+];
+
+// Raccourcis des commandes par identifiant, tels qu'enregistrés dans les préférences
+// (`KeyboardShortcut` n'est pas sérialisable) ; `None` retire le raccourci
+pub type Shortcuts = BTreeMap<String, Option<(Modifiers, Key)>>;
+
+// Raccourcis par défaut de toutes les commandes
+pub fn default_shortcuts() -> Shortcuts {
+    COMMANDS
+        .iter()
+        .map(|command| {
+            let binding = command
+                .default_shortcut
+                .map(|shortcut| (shortcut.modifiers, shortcut.key));
+            (command.id.to_owned(), binding)
+        })
+        .collect()
+}
+
+// Commande d'identifiant `id`
+pub fn find(id: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.id == id)
+}
+
+impl Command {
+    // Icône et nom traduits, séparés
+    pub fn icon_and_name(&self) -> (&'static str, &'static str) {
+        let label = tr(self.label);
+        label.split_once(' ').unwrap_or(("", label))
+    }
+
+    // Raccourci de la commande : celui des préférences, à défaut celui par défaut. Seul accès
+    // aux raccourcis, pour les déclencher comme pour les afficher
+    pub fn shortcut(&self, shortcuts: &Shortcuts) -> Option<KeyboardShortcut> {
+        match shortcuts.get(self.id) {
+            Some(binding) => binding.map(|(modifiers, key)| KeyboardShortcut::new(modifiers, key)),
+            None => self.default_shortcut,
+        }
+    }
+
+    // Nom traduit, suivi du raccourci s'il y en a un
+    pub fn hint(&self, ctx: &egui::Context, shortcuts: &Shortcuts) -> String {
+        let (_, name) = self.icon_and_name();
+        match &self.shortcut(shortcuts) {
+            Some(shortcut) => format!("{name} ({})", ctx.format_shortcut(shortcut)),
+            None => name.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,1682 @@
+// Modèle du dessin : objets validés (traits, rectangles, ellipses, groupes) et test de contact.
+// Les coordonnées sont celles du dessin, indépendantes du zoom et du défilement de la vue.
+
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::sync::mpsc::Receiver;
+
+use epaint::{Color32, Mesh, Pos2, Rect, Shape, Stroke, Tessellator, Vec2, Vertex};
+use serde::{Deserialize, Deserializer, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::event::{DocumentEvent, EventBus};
+use crate::geometry;
+use crate::quadtree::QuadTree;
+
+// Nombre de segments du contour des ellipses
+const ELLIPSE_SEGMENTS: usize = 64;
+// Largeur moyenne d'un caractère, en fraction de la taille du texte
+const TEXT_ADVANCE: f32 = 0.6;
+
+/// Trait validé du dessin. Le rectangle englobant et le maillage ne sont pas sérialisés :
+/// ils sont recalculés à la lecture.
+#[derive(Clone, Serialize)]
+pub struct Line {
+    points: Vec<Pos2>,
+    #[serde(with = "crate::color::hex")]
+    color: Color32,
+    width: f32,
+    opacity: f32, // Appliquée à la couleur au moment du rendu
+    #[serde(skip_serializing_if = "is_false")]
+    erase: bool, // Trait de gomme : efface les traits précédents au lieu de peindre
+    #[serde(skip_serializing_if = "is_false")]
+    closed: bool, // Contour fermé : le dernier point est relié au premier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    swatch: Option<String>, // Entrée de la palette dont le trait suit la couleur
+    #[serde(skip)]
+    rect: Rect, // Rectangle englobant, épaisseur et anticrénelage compris
+    #[serde(skip)]
+    mesh: Option<Mesh>, // Tessellation mise en cache, calculée au premier rendu
+}
+
+impl<'de> Deserialize<'de> for Line {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Champs enregistrés, dans le format produit par `Serialize`
+        #[derive(Deserialize)]
+        struct Saved {
+            points: Vec<Pos2>,
+            #[serde(with = "crate::color::hex")]
+            color: Color32,
+            width: f32,
+            opacity: f32,
+            #[serde(default)]
+            erase: bool,
+            #[serde(default)]
+            closed: bool,
+            #[serde(default)]
+            swatch: Option<String>,
+        }
+        let saved = Saved::deserialize(deserializer)?;
+        Ok(Line {
+            erase: saved.erase,
+            closed: saved.closed,
+            swatch: saved.swatch,
+            ..Line::new(saved.points, saved.color, saved.width, saved.opacity)
+        })
+    }
+}
+
+// Sans le rectangle englobant ni le maillage, qui se déduisent des champs affichés
+impl fmt::Debug for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Line")
+            .field("points", &self.points)
+            .field("color", &self.color)
+            .field("width", &self.width)
+            .field("opacity", &self.opacity)
+            .field("erase", &self.erase)
+            .field("closed", &self.closed)
+            .field("swatch", &self.swatch)
+            .finish_non_exhaustive()
+    }
+}
+
+// Les options désactivées ne sont pas écrites
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl Line {
+    pub fn new(points: Vec<Pos2>, color: Color32, width: f32, opacity: f32) -> Self {
+        Self {
+            rect: Rect::from_points(&points).expand(width / 2.0 + 1.0),
+            points,
+            color,
+            width,
+            opacity,
+            erase: false,
+            closed: false,
+            swatch: None,
+            mesh: None,
+        }
+    }
+
+    /// Fait du trait un trait de gomme, ou un trait normal.
+    pub fn with_erase(mut self, erase: bool) -> Self {
+        self.erase = erase;
+        self
+    }
+
+    pub fn points(&self) -> &[Pos2] {
+        &self.points
+    }
+
+    pub fn color(&self) -> Color32 {
+        self.color
+    }
+
+    /// Fait suivre au trait la couleur de l'entrée de palette `swatch` du document qui le
+    /// contiendra, ou (`None`) en fait une couleur libre.
+    pub fn with_swatch(mut self, swatch: Option<String>) -> Self {
+        self.swatch = swatch;
+        self
+    }
+
+    /// Nom de l'entrée de palette dont le trait suit la couleur.
+    pub fn swatch(&self) -> Option<&str> {
+        self.swatch.as_deref()
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Vrai pour un trait de gomme.
+    pub fn is_eraser(&self) -> bool {
+        self.erase
+    }
+
+    /// Vrai si le dernier point est relié au premier.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Rectangle englobant, épaisseur et anticrénelage compris.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    // Copie dans la couleur libre `color`, qui ne suit plus la palette
+    fn recolored(&self, color: Color32) -> Self {
+        Self {
+            color,
+            swatch: None,
+            mesh: None,
+            ..self.clone()
+        }
+    }
+
+    // Copie déplacée de `offset`
+    fn translated(&self, offset: Vec2) -> Self {
+        Self {
+            points: self.points.iter().map(|&point| point + offset).collect(),
+            rect: self.rect.translate(offset),
+            mesh: None,
+            ..self.clone()
+        }
+    }
+
+    /// Calcule le maillage du trait, sans passer par le cache.
+    pub fn tessellate(&self, tessellator: &mut Tessellator) -> Mesh {
+        let stroke = Stroke::new(self.width, self.color.gamma_multiply(self.opacity));
+        let shape = if self.closed {
+            Shape::closed_line(self.points.clone(), stroke)
+        } else {
+            Shape::line(self.points.clone(), stroke)
+        };
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_shape(shape, &mut mesh);
+        mesh
+    }
+
+    /// Retourne le maillage du trait, en le tessellant seulement s'il n'est pas en cache.
+    pub fn mesh(&mut self, tessellator: &mut Tessellator) -> &Mesh {
+        let mesh = match self.mesh.take() {
+            Some(mesh) => mesh,
+            None => self.tessellate(tessellator),
+        };
+        self.mesh.insert(mesh)
+    }
+
+    /// Oublie le maillage en cache, par exemple quand la résolution de rendu change.
+    pub fn clear_mesh(&mut self) {
+        self.mesh = None;
+    }
+
+    // Segments du trait, y compris celui qui ferme un contour ; un trait d'un seul point
+    // est un segment de longueur nulle
+    fn segments(&self) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+        let closing = match (self.closed, self.points.first(), self.points.last()) {
+            (true, Some(&first), Some(&last)) => Some((last, first)),
+            _ => None,
+        };
+        let dot = match self.points[..] {
+            [point] => Some((point, point)),
+            _ => None,
+        };
+        self.points
+            .windows(2)
+            .map(|segment| (segment[0], segment[1]))
+            .chain(closing)
+            .chain(dot)
+    }
+
+    /// Vrai si `pos` est sur le trait, à la demi-épaisseur près.
+    pub fn contains(&self, pos: Pos2) -> bool {
+        self.intersects_circle(pos, 0.0)
+    }
+
+    /// Vrai si le trait, épaisseur comprise, touche le disque de centre `center`
+    /// et de rayon `radius`.
+    pub fn intersects_circle(&self, center: Pos2, radius: f32) -> bool {
+        let reach = radius + self.width / 2.0;
+        self.rect.expand(radius).contains(center)
+            && self
+                .segments()
+                .any(|(a, b)| geometry::segment_intersects_circle(a, b, center, reach))
+    }
+
+    /// Vrai si le trait, épaisseur comprise, touche `rect`, même sans avoir de point
+    /// à l'intérieur.
+    pub fn intersects_rect(&self, rect: Rect) -> bool {
+        let reach = self.width / 2.0;
+        self.rect.intersects(rect.expand(reach))
+            && self
+                .segments()
+                .any(|(a, b)| geometry::distance_segment_to_rect(a, b, rect) <= reach)
+    }
+
+    /// Vrai si le trait, épaisseur comprise, touche le polygone fermé `polygon`
+    /// (sélection au lasso).
+    pub fn intersects_polygon(&self, polygon: &[Pos2]) -> bool {
+        let reach = self.width / 2.0;
+        self.rect
+            .intersects(Rect::from_points(polygon).expand(reach))
+            && self
+                .segments()
+                .any(|(a, b)| geometry::distance_segment_to_polygon(a, b, polygon) <= reach)
+    }
+
+    /// Estimation de la mémoire occupée par le trait (points et maillage en cache).
+    pub fn memory_size(&self) -> usize {
+        let mesh_size = self.mesh.as_ref().map_or(0, |mesh| {
+            mesh.vertices.capacity() * std::mem::size_of::<Vertex>()
+                + mesh.indices.capacity() * std::mem::size_of::<u32>()
+        });
+        std::mem::size_of::<Self>()
+            + self.points.capacity() * std::mem::size_of::<Pos2>()
+            + mesh_size
+    }
+}
+
+// Date actuelle en millisecondes depuis l'époque Unix
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Forme d'un objet du dessin. Chaque forme est rendue par un ou plusieurs traits, qui
+/// portent son maillage en cache : le rendu et le test de contact passent par eux.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Figure {
+    /// Trait à main levée ou segment.
+    Stroke(Line),
+    /// Rectangle aux côtés alignés sur les axes, tracé par son contour.
+    Rect { rect: Rect, outline: Line },
+    /// Ellipse inscrite dans `rect`, tracée par son contour.
+    Ellipse { rect: Rect, outline: Line },
+    /// Texte dont le coin haut gauche est `pos`, de `size` unités de haut. Le canevas ne
+    /// sait pas encore rendre le texte : `outline` en trace le cadre, à la place.
+    Text {
+        pos: Pos2,
+        text: String,
+        size: f32,
+        outline: Line,
+    },
+    /// Image de fichier `source` (chemin ou URL) affichée dans `rect`. Le canevas ne sait
+    /// pas encore charger les images : `outline` trace le cadre barré, à la place.
+    Image {
+        rect: Rect,
+        source: String,
+        outline: Line,
+    },
+    /// Objets regroupés, sélectionnés et modifiés ensemble.
+    Group(Vec<DrawObject>),
+}
+
+// Coins de `rect`, dans le sens du contour
+fn corners(rect: Rect) -> Vec<Pos2> {
+    vec![
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ]
+}
+
+/// Informations d'un objet, indépendantes de sa forme. L'identifiant, l'auteur et la date
+/// sont attribués par le document quand l'objet y est ajouté pour la première fois.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub id: u64,         // Identifiant stable dans le document ; 0 avant l'ajout
+    pub author: String,  // Auteur du document au moment de l'ajout
+    pub created_at: u64, // Date de l'ajout, en millisecondes depuis l'époque Unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>, // Nom donné par l'utilisateur
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool, // Protégé de `erase_at` et des actions du menu contextuel
+}
+
+/// Objet du dessin : sa forme et ses métadonnées.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrawObject {
+    #[serde(flatten)]
+    figure: Figure,
+    meta: Metadata,
+}
+
+impl DrawObject {
+    pub fn new(figure: Figure) -> Self {
+        Self {
+            figure,
+            meta: Metadata::default(),
+        }
+    }
+
+    /// Rectangle de contour `rect`.
+    pub fn rect(rect: Rect, color: Color32, width: f32, opacity: f32) -> Self {
+        let outline = Line {
+            closed: true,
+            ..Line::new(corners(rect), color, width, opacity)
+        };
+        Self::new(Figure::Rect { rect, outline })
+    }
+
+    /// Ellipse inscrite dans `rect`.
+    pub fn ellipse(rect: Rect, color: Color32, width: f32, opacity: f32) -> Self {
+        let (center, radius) = (rect.center(), rect.size() / 2.0);
+        let points = (0..ELLIPSE_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * TAU;
+                center + radius * Vec2::angled(angle)
+            })
+            .collect();
+        let outline = Line {
+            closed: true,
+            ..Line::new(points, color, width, opacity)
+        };
+        Self::new(Figure::Ellipse { rect, outline })
+    }
+
+    /// Texte `text` dont le coin haut gauche est `pos`, de `size` unités de haut. Sa largeur
+    /// est estimée d'après le nombre de caractères de la plus longue ligne.
+    pub fn text(pos: Pos2, text: impl Into<String>, size: f32, color: Color32) -> Self {
+        let text = text.into();
+        let lines = text.lines().count().max(1);
+        let columns = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let rect = Rect::from_min_size(
+            pos,
+            Vec2::new(columns as f32 * TEXT_ADVANCE * size, lines as f32 * size),
+        );
+        let outline = Line {
+            closed: true,
+            ..Line::new(corners(rect), color, 1.0, 1.0)
+        };
+        Self::new(Figure::Text {
+            pos,
+            text,
+            size,
+            outline,
+        })
+    }
+
+    /// Image de fichier `source` affichée dans `rect`.
+    pub fn image(rect: Rect, source: impl Into<String>) -> Self {
+        // Contour puis diagonales, d'un seul tracé
+        let mut points = corners(rect);
+        points.extend([
+            rect.left_top(),
+            rect.right_bottom(),
+            rect.right_top(),
+            rect.left_bottom(),
+        ]);
+        let outline = Line::new(points, Color32::GRAY, 1.0, 1.0);
+        Self::new(Figure::Image {
+            rect,
+            source: source.into(),
+            outline,
+        })
+    }
+
+    /// Groupe des objets `objects`, qui gardent leurs propres métadonnées.
+    pub fn group(objects: Vec<DrawObject>) -> Self {
+        Self::new(Figure::Group(objects))
+    }
+
+    /// Donne un nom à l'objet.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.meta.name = Some(name.into());
+        self
+    }
+
+    pub fn figure(&self) -> &Figure {
+        &self.figure
+    }
+
+    pub fn meta(&self) -> &Metadata {
+        &self.meta
+    }
+
+    /// Nom du type de l'objet, en français.
+    pub fn kind_name(&self) -> &'static str {
+        match &self.figure {
+            Figure::Stroke(line) if line.erase => "Gomme",
+            Figure::Stroke(_) => "Trait",
+            Figure::Rect { .. } => "Rectangle",
+            Figure::Ellipse { .. } => "Ellipse",
+            Figure::Text { .. } => "Texte",
+            Figure::Image { .. } => "Image",
+            Figure::Group(_) => "Groupe",
+        }
+    }
+
+    /// Traits qui rendent l'objet, du dessous au dessus.
+    pub fn lines(&self) -> Vec<&Line> {
+        match &self.figure {
+            Figure::Stroke(line)
+            | Figure::Rect { outline: line, .. }
+            | Figure::Ellipse { outline: line, .. }
+            | Figure::Text { outline: line, .. }
+            | Figure::Image { outline: line, .. } => vec![line],
+            Figure::Group(objects) => objects.iter().flat_map(DrawObject::lines).collect(),
+        }
+    }
+
+    /// Accès aux traits de l'objet pour remplir leur cache de tessellation.
+    pub fn lines_mut(&mut self) -> Vec<&mut Line> {
+        match &mut self.figure {
+            Figure::Stroke(line)
+            | Figure::Rect { outline: line, .. }
+            | Figure::Ellipse { outline: line, .. }
+            | Figure::Text { outline: line, .. }
+            | Figure::Image { outline: line, .. } => vec![line],
+            Figure::Group(objects) => objects.iter_mut().flat_map(DrawObject::lines_mut).collect(),
+        }
+    }
+
+    /// Fait suivre à tous les traits de l'objet, sauf ceux de gomme, la couleur de l'entrée
+    /// de palette `swatch`.
+    pub fn with_swatch(mut self, swatch: &str) -> Self {
+        for line in self.lines_mut() {
+            if !line.erase {
+                line.swatch = Some(swatch.to_owned());
+            }
+        }
+        self
+    }
+
+    /// Rectangle englobant, épaisseur et anticrénelage compris.
+    pub fn bounds(&self) -> Rect {
+        self.lines()
+            .iter()
+            .fold(Rect::NOTHING, |bounds, line| bounds.union(line.rect))
+    }
+
+    /// Vrai si `pos` est sur l'un des traits de l'objet.
+    pub fn contains(&self, pos: Pos2) -> bool {
+        self.hit(pos, 0.0)
+    }
+
+    /// Vrai si un des traits de l'objet, épaisseur comprise, passe à moins de `tolerance`
+    /// de `pos` (voir [`geometry::hit_tolerance`]). Un texte ou une image est touché dans
+    /// tout son cadre.
+    pub fn hit(&self, pos: Pos2, tolerance: f32) -> bool {
+        match &self.figure {
+            _ if !self.bounds().expand(tolerance).contains(pos) => false,
+            Figure::Text { .. } | Figure::Image { .. } => true,
+            Figure::Group(objects) => objects.iter().any(|object| object.hit(pos, tolerance)),
+            _ => self
+                .lines()
+                .iter()
+                .any(|line| line.intersects_circle(pos, tolerance)),
+        }
+    }
+
+    /// Vrai pour un trait de gomme ; les autres objets peignent toujours.
+    pub fn is_eraser(&self) -> bool {
+        matches!(&self.figure, Figure::Stroke(line) if line.erase)
+    }
+
+    /// Couleur de l'objet, ou `None` pour un groupe dont les traits n'ont pas tous la même.
+    pub fn color(&self) -> Option<Color32> {
+        let lines = self.lines();
+        let color = lines.first()?.color;
+        lines
+            .iter()
+            .all(|line| line.color == color)
+            .then_some(color)
+    }
+
+    /// Même objet dans une autre couleur ; un groupe change la couleur de tous ses traits.
+    /// Les métadonnées sont conservées.
+    pub fn recolored(&self, color: Color32) -> Self {
+        let figure = match &self.figure {
+            Figure::Stroke(line) => Figure::Stroke(line.recolored(color)),
+            Figure::Rect { rect, outline } => Figure::Rect {
+                rect: *rect,
+                outline: outline.recolored(color),
+            },
+            Figure::Ellipse { rect, outline } => Figure::Ellipse {
+                rect: *rect,
+                outline: outline.recolored(color),
+            },
+            Figure::Text {
+                pos,
+                text,
+                size,
+                outline,
+            } => Figure::Text {
+                pos: *pos,
+                text: text.clone(),
+                size: *size,
+                outline: outline.recolored(color),
+            },
+            // Une image garde ses couleurs
+            Figure::Image { .. } => self.figure.clone(),
+            Figure::Group(objects) => Figure::Group(
+                objects
+                    .iter()
+                    .map(|object| object.recolored(color))
+                    .collect(),
+            ),
+        };
+        Self {
+            figure,
+            meta: self.meta.clone(),
+        }
+    }
+
+    /// Copie de l'objet déplacée de `offset`, pour la coller : seul le nom est conservé,
+    /// le document attribue à la copie et aux objets de son groupe de nouvelles métadonnées.
+    pub fn duplicate(&self, offset: Vec2) -> Self {
+        let figure = match &self.figure {
+            Figure::Stroke(line) => Figure::Stroke(line.translated(offset)),
+            Figure::Rect { rect, outline } => Figure::Rect {
+                rect: rect.translate(offset),
+                outline: outline.translated(offset),
+            },
+            Figure::Ellipse { rect, outline } => Figure::Ellipse {
+                rect: rect.translate(offset),
+                outline: outline.translated(offset),
+            },
+            Figure::Text {
+                pos,
+                text,
+                size,
+                outline,
+            } => Figure::Text {
+                pos: *pos + offset,
+                text: text.clone(),
+                size: *size,
+                outline: outline.translated(offset),
+            },
+            Figure::Image {
+                rect,
+                source,
+                outline,
+            } => Figure::Image {
+                rect: rect.translate(offset),
+                source: source.clone(),
+                outline: outline.translated(offset),
+            },
+            Figure::Group(objects) => Figure::Group(
+                objects
+                    .iter()
+                    .map(|object| object.duplicate(offset))
+                    .collect(),
+            ),
+        };
+        Self {
+            figure,
+            meta: Metadata {
+                name: self.meta.name.clone(),
+                ..Metadata::default()
+            },
+        }
+    }
+
+    /// Nombre total de points des traits de l'objet.
+    pub fn point_count(&self) -> usize {
+        self.lines().iter().map(|line| line.points.len()).sum()
+    }
+
+    /// Estimation de la mémoire occupée par l'objet (traits et maillages en cache).
+    pub fn memory_size(&self) -> usize {
+        let meta =
+            self.meta.author.capacity() + self.meta.name.as_ref().map_or(0, String::capacity);
+        meta + match &self.figure {
+            Figure::Stroke(line)
+            | Figure::Rect { outline: line, .. }
+            | Figure::Ellipse { outline: line, .. } => line.memory_size(),
+            Figure::Text { text, outline, .. } => text.capacity() + outline.memory_size(),
+            Figure::Image {
+                source, outline, ..
+            } => source.capacity() + outline.memory_size(),
+            Figure::Group(objects) => {
+                objects.capacity() * std::mem::size_of::<Self>()
+                    + objects.iter().map(DrawObject::memory_size).sum::<usize>()
+            }
+        }
+    }
+
+    // Attribue identifiant, auteur et date à l'objet et à ceux de son groupe qui n'en ont
+    // pas encore ; `next_id` est le prochain identifiant libre
+    fn stamp(&mut self, next_id: &mut u64, author: &str, now: u64) {
+        if self.meta.id == 0 {
+            self.meta = Metadata {
+                id: *next_id,
+                author: author.to_owned(),
+                created_at: now,
+                name: self.meta.name.take(),
+                locked: self.meta.locked,
+            };
+        }
+        // Un fichier lu peut contenir n'importe quel identifiant, y compris le plus grand
+        *next_id = (*next_id).max(self.meta.id.saturating_add(1));
+        if let Figure::Group(objects) = &mut self.figure {
+            for object in objects {
+                object.stamp(next_id, author, now);
+            }
+        }
+    }
+}
+
+impl From<Line> for DrawObject {
+    fn from(line: Line) -> Self {
+        Self::new(Figure::Stroke(line))
+    }
+}
+
+/// Modification élémentaire du dessin. Appliquer une action produit l'action inverse,
+/// qui est conservée dans l'historique pour l'annuler.
+// `Push` porte l'objet entier : le mettre dans une `Box` alourdirait chaque création
+// d'action pour quelques octets par entrée de l'historique
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Ajoute un objet au-dessus des autres.
+    Push(DrawObject),
+    /// Retire le dernier objet.
+    Pop,
+    /// Insère des objets ; chaque indice est celui de l'objet une fois l'insertion faite.
+    Insert(Vec<(usize, DrawObject)>),
+    /// Retire les objets aux indices donnés.
+    Remove(Vec<usize>),
+    /// Renomme l'objet à l'indice donné ; `None` retire son nom.
+    Rename(usize, Option<String>),
+    /// Verrouille (`true`) ou déverrouille l'objet à l'indice donné.
+    Lock(usize, bool),
+    /// Crée ou modifie l'entrée de palette nommée, ou la retire (`None`). Les traits qui y
+    /// font référence prennent sa nouvelle couleur.
+    SetSwatch(
+        String,
+        #[serde(with = "crate::color::hex_option")] Option<Color32>,
+    ),
+    /// Applique plusieurs actions dans l'ordre, annulées ensemble.
+    Batch(Vec<Action>),
+}
+
+impl Action {
+    // Nom de l'action dans le journal
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Push(_) => "push",
+            Action::Pop => "pop",
+            Action::Insert(_) => "insert",
+            Action::Remove(_) => "remove",
+            Action::Rename(..) => "rename",
+            Action::Lock(..) => "lock",
+            Action::SetSwatch(..) => "set_swatch",
+            Action::Batch(_) => "batch",
+        }
+    }
+
+    /// Estimation de la mémoire occupée par les objets de l'action.
+    pub fn memory_size(&self) -> usize {
+        let objects = match self {
+            Action::Push(object) => object.memory_size(),
+            Action::Insert(objects) => objects.iter().map(|(_, object)| object.memory_size()).sum(),
+            Action::Batch(actions) => actions.iter().map(Action::memory_size).sum(),
+            Action::Rename(_, name) => name.as_ref().map_or(0, String::capacity),
+            Action::SetSwatch(name, _) => name.capacity(),
+            Action::Pop | Action::Remove(_) | Action::Lock(..) => 0,
+        };
+        std::mem::size_of::<Self>() + objects
+    }
+}
+
+/// Entrée nommée de la palette du document, dont les traits peuvent suivre la couleur.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Swatch {
+    pub name: String,
+    #[serde(with = "crate::color::hex")]
+    pub color: Color32,
+}
+
+// Couleur de l'entrée `name` de `palette`
+fn swatch_color(palette: &[Swatch], name: &str) -> Option<Color32> {
+    palette
+        .iter()
+        .find(|swatch| swatch.name == name)
+        .map(|swatch| swatch.color)
+}
+
+// Couleur que `palette` impose au trait `line`, si elle diffère de la sienne
+fn palette_color(line: &Line, palette: &[Swatch]) -> Option<Color32> {
+    let color = swatch_color(palette, line.swatch.as_deref()?)?;
+    (color != line.color).then_some(color)
+}
+
+// Donne aux traits de `object` qui font référence à une entrée de `palette` sa couleur
+fn follow_palette(palette: &[Swatch], object: &mut DrawObject) {
+    for line in object.lines_mut() {
+        if let Some(color) = palette_color(line, palette) {
+            line.color = color;
+            line.mesh = None;
+        }
+    }
+}
+
+/// Propriétés du document : description et réglages du canevas, enregistrés avec le dessin
+/// et repris dans les exports. Les dates sont en millisecondes depuis l'époque Unix,
+/// 0 si elles sont inconnues (fichiers écrits avant leur ajout).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Properties {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    /// Auteur du document, qui peut différer de celui des objets.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub created_at: u64,
+    /// Date de la dernière modification, du dessin ou des propriétés.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub modified_at: u64,
+    /// Fond du canevas ; `None` pour celui choisi dans les préférences.
+    #[serde(
+        with = "crate::color::hex_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub background: Option<Color32>,
+    /// Format du dessin, depuis l'origine : cadre affiché sur le canevas et zone exportée ;
+    /// `None` pour un canevas sans limites, exporté au plus près des objets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Vec2>,
+}
+
+impl Properties {
+    /// Vrai si rien n'a été renseigné ; les propriétés vides ne sont pas enregistrées.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Date `millis` (depuis l'époque Unix) au format ISO 8601, en temps universel :
+/// « 2024-03-05T14:07:00Z ».
+pub fn iso_8601(millis: u64) -> String {
+    let seconds = millis / 1000;
+    let time = seconds % 86_400;
+    // Jours depuis le 1er mars de l'an 0, puis année, mois et jour du calendrier grégorien
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+    )
+}
+
+/// Dessin et historique d'annulation, manipulables sans interface graphique. L'interface
+/// ne modifie le dessin qu'à travers ces méthodes. Seuls les objets, l'auteur, le prochain
+/// identifiant, la palette et les propriétés sont sérialisés ; l'index spatial est
+/// reconstruit à la lecture.
+#[derive(Default, Serialize)]
+pub struct Document {
+    objects: Vec<DrawObject>,
+    #[serde(skip)]
+    index: QuadTree, // Emprises des objets, suivies à chaque action
+    #[serde(skip)]
+    undo_stack: Vec<Action>, // Actions inverses des modifications faites
+    #[serde(skip)]
+    redo_stack: Vec<Action>, // Actions inverses des annulations
+    #[serde(skip)]
+    changed_from: Option<usize>, // Plus petit indice modifié depuis `take_changed_from`
+    #[serde(default)]
+    author: String, // Auteur des objets ajoutés
+    next_id: u64, // Identifiant du prochain objet ajouté
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    palette: Vec<Swatch>, // Couleurs nommées, par ordre alphabétique
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    properties: Properties,
+    #[serde(skip)]
+    events: EventBus<DocumentEvent>, // Abonnés aux modifications
+}
+
+// Identifiants uniques et non nuls pour des objets lus, même si le fichier a été modifié à
+// la main : un objet sans identifiant ou dont l'identifiant est déjà pris en reçoit un
+// nouveau. Retourne le prochain identifiant libre, au-delà de tous ceux du dessin
+fn repair_ids(objects: &mut [DrawObject], mut next_id: u64) -> u64 {
+    fn max_id(objects: &[DrawObject]) -> u64 {
+        objects
+            .iter()
+            .map(|object| match &object.figure {
+                Figure::Group(objects) => object.meta.id.max(max_id(objects)),
+                _ => object.meta.id,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    fn renumber(objects: &mut [DrawObject], seen: &mut HashSet<u64>, next_id: &mut u64) {
+        for object in objects {
+            if object.meta.id == 0 || !seen.insert(object.meta.id) {
+                object.meta.id = *next_id;
+                seen.insert(*next_id);
+                *next_id = next_id.saturating_add(1);
+            }
+            if let Figure::Group(objects) = &mut object.figure {
+                renumber(objects, seen, next_id);
+            }
+        }
+    }
+    // Au moins 1 dès qu'il y a un objet : 0 veut dire « sans identifiant »
+    if !objects.is_empty() {
+        next_id = next_id.max(max_id(objects).saturating_add(1));
+    }
+    renumber(objects, &mut HashSet::new(), &mut next_id);
+    next_id
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Champs enregistrés, dans le format produit par `Serialize`
+        #[derive(Deserialize)]
+        struct Saved {
+            objects: Vec<DrawObject>,
+            #[serde(default)]
+            author: String,
+            next_id: u64,
+            #[serde(default)]
+            palette: Vec<Swatch>,
+            #[serde(default)]
+            properties: Properties,
+        }
+        let mut saved = Saved::deserialize(deserializer)?;
+        let next_id = repair_ids(&mut saved.objects, saved.next_id);
+        let mut document = Document::with_objects(saved.objects, saved.author, next_id);
+        // Triée et sans doublon, même si le fichier a été modifié à la main
+        document.palette = saved.palette;
+        document.palette.sort_by(|a, b| a.name.cmp(&b.name));
+        document.palette.dedup_by(|a, b| a.name == b.name);
+        document.properties = saved.properties;
+        Ok(document)
+    }
+}
+
+impl Document {
+    /// Document vide, créé maintenant.
+    pub fn new() -> Self {
+        Self {
+            properties: Properties {
+                created_at: now_millis(),
+                ..Properties::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Document vide dont les objets ajoutés auront `author` pour auteur ; c'est aussi
+    /// l'auteur indiqué dans ses propriétés.
+    pub fn with_author(author: impl Into<String>) -> Self {
+        let author = author.into();
+        let mut document = Self::new();
+        document.properties.author = author.clone();
+        document.author = author;
+        document
+    }
+
+    /// Document contenant déjà `objects`, sans historique. Les objets sans identifiant
+    /// en reçoivent un.
+    pub fn from_objects(mut objects: Vec<DrawObject>) -> Self {
+        let mut next_id = 1;
+        let now = now_millis();
+        for object in &mut objects {
+            object.stamp(&mut next_id, "", now);
+        }
+        let mut document = Self::with_objects(objects, String::new(), next_id);
+        document.properties.created_at = now;
+        document
+    }
+
+    // Document sans historique, dont l'index est construit et dont tous les objets sont
+    // à rastériser : il n'a encore jamais été rendu
+    fn with_objects(objects: Vec<DrawObject>, author: String, next_id: u64) -> Self {
+        let mut index = QuadTree::default();
+        for (position, object) in objects.iter().enumerate() {
+            index.insert(position, object.bounds());
+        }
+        Self {
+            objects,
+            index,
+            changed_from: Some(0),
+            author,
+            next_id,
+            ..Self::default()
+        }
+    }
+
+    /// Copie des objets, de l'auteur, de la palette et des propriétés, sans historique.
+    /// Les objets ajoutés ensuite à la copie reçoivent les mêmes identifiants qu'ils
+    /// auraient reçus dans l'original.
+    pub fn fork(&self) -> Self {
+        Self {
+            changed_from: Some(0),
+            objects: self.objects.clone(),
+            index: self.index.clone(),
+            author: self.author.clone(),
+            next_id: self.next_id,
+            palette: self.palette.clone(),
+            properties: self.properties.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Document vide qui reprend l'auteur, la palette et les propriétés : nouvelle image
+    /// d'une animation.
+    pub fn fork_empty(&self) -> Self {
+        Self {
+            author: self.author.clone(),
+            palette: self.palette.clone(),
+            properties: self.properties.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Abonnement aux modifications du document. Toutes passent par les actions,
+    /// y compris les annulations : aucune n'échappe aux abonnés.
+    pub fn subscribe(&mut self) -> Receiver<DocumentEvent> {
+        self.events.subscribe()
+    }
+
+    /// Auteur des objets ajoutés.
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn set_author(&mut self, author: impl Into<String>) {
+        self.author = author.into();
+    }
+
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Zone du format du dessin, s'il en a un.
+    pub fn page(&self) -> Option<Rect> {
+        let size = self.properties.size?;
+        Some(Rect::from_min_size(Pos2::ZERO, size))
+    }
+
+    /// Remplace les propriétés, hors historique. Les dates ne se modifient pas : la date
+    /// de création est conservée et la modification est datée de maintenant.
+    pub fn set_properties(&mut self, properties: Properties) {
+        self.properties = Properties {
+            created_at: self.properties.created_at,
+            modified_at: now_millis(),
+            ..properties
+        };
+    }
+
+    /// Palette nommée du document, par ordre alphabétique.
+    pub fn palette(&self) -> &[Swatch] {
+        &self.palette
+    }
+
+    /// Couleur de l'entrée de palette `name`.
+    pub fn swatch(&self, name: &str) -> Option<Color32> {
+        swatch_color(&self.palette, name)
+    }
+
+    /// Crée l'entrée de palette `name` ou change sa couleur (annulable) : tous les traits
+    /// qui y font référence la prennent. Retourne `false` si rien ne change.
+    pub fn set_swatch(&mut self, name: &str, color: Color32) -> bool {
+        let name = name.trim();
+        !name.is_empty() && self.apply(Action::SetSwatch(name.to_owned(), Some(color)))
+    }
+
+    /// Retire l'entrée `name` de la palette (annulable) ; les traits qui y faisaient
+    /// référence gardent leur couleur, et la reprennent de l'entrée recréée sous ce nom.
+    pub fn remove_swatch(&mut self, name: &str) -> bool {
+        self.apply(Action::SetSwatch(name.to_owned(), None))
+    }
+
+    /// Crée, modifie ou (`None`) retire la couleur nommée `name` dans tous les `documents`,
+    /// par exemple les images d'une animation, qui gardent ainsi la même palette. Chaque
+    /// document peut l'annuler dans son propre historique. Retourne `true` si l'un d'eux
+    /// a changé.
+    pub fn set_swatch_everywhere<'a>(
+        documents: impl IntoIterator<Item = &'a mut Document>,
+        name: &str,
+        color: Option<Color32>,
+    ) -> bool {
+        let name = name.trim();
+        if name.is_empty() {
+            return false;
+        }
+        documents.into_iter().fold(false, |changed, document| {
+            document.apply(Action::SetSwatch(name.to_owned(), color)) | changed
+        })
+    }
+
+    /// Indice de l'objet d'identifiant `id`.
+    pub fn position(&self, id: u64) -> Option<usize> {
+        self.objects.iter().position(|object| object.meta.id == id)
+    }
+
+    /// Renomme l'objet à l'indice `index` (annulable) ; un nom vide retire le nom.
+    pub fn rename(&mut self, index: usize, name: &str) -> bool {
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        self.apply(Action::Rename(index, name.map(str::to_owned)))
+    }
+
+    /// Verrouille ou déverrouille l'objet à l'indice `index` (annulable).
+    pub fn set_locked(&mut self, index: usize, locked: bool) -> bool {
+        self.apply(Action::Lock(index, locked))
+    }
+
+    /// Objets du dessin, du plus ancien (dessous) au plus récent (dessus).
+    pub fn objects(&self) -> &[DrawObject] {
+        &self.objects
+    }
+
+    /// Accès aux objets pour remplir le cache de tessellation de leurs traits.
+    pub fn objects_mut(&mut self) -> &mut [DrawObject] {
+        &mut self.objects
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Applique `action` et l'enregistre dans l'historique ; le rétablissement est vidé.
+    /// Retourne `false`, sans rien modifier, si l'action est impossible.
+    #[tracing::instrument(level = "debug", skip_all, fields(action = action.name()))]
+    pub fn apply(&mut self, action: Action) -> bool {
+        let Some(inverse) = self.execute(action) else {
+            tracing::debug!("action impossible");
+            return false;
+        };
+        tracing::debug!(objects = self.objects.len(), "action appliquée");
+        self.properties.modified_at = now_millis();
+        self.undo_stack.push(inverse);
+        self.redo_stack.clear();
+        true
+    }
+
+    /// Ajoute un trait au-dessus des autres.
+    pub fn add_stroke(&mut self, line: Line) {
+        self.apply(Action::Push(line.into()));
+    }
+
+    /// Indices des objets dont l'emprise touche `rect`, du dessous au dessus :
+    /// ceux à rendre dans une vue.
+    pub fn objects_in(&self, rect: Rect) -> Vec<usize> {
+        self.index
+            .query(rect)
+            .into_iter()
+            .filter(|&index| self.objects[index].bounds().intersects(rect))
+            .collect()
+    }
+
+    /// Indices des objets passant à moins de `tolerance` de `pos`, du dessous au dessus.
+    pub fn objects_at(&self, pos: Pos2, tolerance: f32) -> Vec<usize> {
+        self.candidates_at(pos, tolerance)
+            .into_iter()
+            .filter(|&index| self.objects[index].hit(pos, tolerance))
+            .collect()
+    }
+
+    /// Indice de l'objet le plus haut passant à moins de `tolerance` de `pos`.
+    pub fn top_object_at(&self, pos: Pos2, tolerance: f32) -> Option<usize> {
+        self.candidates_at(pos, tolerance)
+            .into_iter()
+            .rev()
+            .find(|&index| self.objects[index].hit(pos, tolerance))
+    }
+
+    // Objets que l'index place assez près de `pos` pour le toucher
+    fn candidates_at(&self, pos: Pos2, tolerance: f32) -> Vec<usize> {
+        let area = Rect::from_center_size(pos, Vec2::splat(2.0 * tolerance.max(0.0)));
+        self.index.query(area)
+    }
+
+    /// Supprime tous les objets non verrouillés passant à moins de `tolerance` de `pos` et
+    /// retourne leur nombre.
+    pub fn erase_at(&mut self, pos: Pos2, tolerance: f32) -> usize {
+        let mut hits = self.objects_at(pos, tolerance);
+        hits.retain(|&index| !self.objects[index].meta.locked);
+        let count = hits.len();
+        self.apply(Action::Remove(hits));
+        count
+    }
+
+    /// Indices des objets touchés par le rectangle `rect` (sélection au cadre) : un trait
+    /// qui le traverse est retenu même si aucun de ses points n'est à l'intérieur.
+    pub fn select_rect(&self, rect: Rect) -> Vec<usize> {
+        self.select(rect, |line| line.intersects_rect(rect))
+    }
+
+    /// Indices des objets touchés par le polygone fermé `polygon` (sélection au lasso).
+    pub fn select_lasso(&self, polygon: &[Pos2]) -> Vec<usize> {
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+        self.select(Rect::from_points(polygon), |line| {
+            line.intersects_polygon(polygon)
+        })
+    }
+
+    // Indices des objets de la zone `area` dont au moins un trait vérifie `hit`
+    fn select(&self, area: Rect, hit: impl Fn(&Line) -> bool) -> Vec<usize> {
+        self.index
+            .query(area)
+            .into_iter()
+            .filter(|&index| self.objects[index].lines().into_iter().any(&hit))
+            .collect()
+    }
+
+    /// Supprime tous les objets (annulable).
+    pub fn clear(&mut self) {
+        self.apply(Action::Remove((0..self.objects.len()).collect()));
+    }
+
+    /// Annule la dernière modification ; retourne `false` s'il n'y en a pas.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn undo(&mut self) -> bool {
+        let Some(action) = self.undo_stack.pop() else {
+            return false;
+        };
+        let name = action.name();
+        match self.execute(action) {
+            Some(inverse) => {
+                tracing::debug!(action = name, "modification annulée");
+                self.properties.modified_at = now_millis();
+                self.redo_stack.push(inverse);
+            }
+            None => tracing::warn!(
+                action = name,
+                "historique incohérent : annulation impossible"
+            ),
+        }
+        true
+    }
+
+    /// Rétablit la dernière modification annulée ; retourne `false` s'il n'y en a pas.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn redo(&mut self) -> bool {
+        let Some(action) = self.redo_stack.pop() else {
+            return false;
+        };
+        let name = action.name();
+        match self.execute(action) {
+            Some(inverse) => {
+                tracing::debug!(action = name, "modification rétablie");
+                self.properties.modified_at = now_millis();
+                self.undo_stack.push(inverse);
+            }
+            None => tracing::warn!(
+                action = name,
+                "historique incohérent : rétablissement impossible"
+            ),
+        }
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Oublie les modifications annulées, qui ne pourront plus être rétablies.
+    pub fn clear_redo(&mut self) {
+        self.redo_stack.clear();
+    }
+
+    /// Oublie tout l'historique : plus rien ne pourra être annulé ni rétabli.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Oublie les maillages en cache de tous les traits.
+    pub fn clear_meshes(&mut self) {
+        for object in &mut self.objects {
+            for line in object.lines_mut() {
+                line.clear_mesh();
+            }
+        }
+    }
+
+    /// Mémoire occupée par les objets du dessin.
+    pub fn memory_size(&self) -> usize {
+        self.objects.iter().map(DrawObject::memory_size).sum()
+    }
+
+    /// Mémoire occupée par les modifications qui peuvent être annulées ; les objets
+    /// supprimés y restent, pour être rendus par l'annulation.
+    pub fn undo_memory_size(&self) -> usize {
+        self.undo_stack.iter().map(Action::memory_size).sum()
+    }
+
+    /// Mémoire occupée par les modifications qui peuvent être rétablies.
+    pub fn redo_memory_size(&self) -> usize {
+        self.redo_stack.iter().map(Action::memory_size).sum()
+    }
+
+    /// Plus petit indice d'objet modifié depuis le dernier appel, ou `None` si le dessin
+    /// n'a pas changé : les objets d'indice inférieur sont restés identiques.
+    pub fn take_changed_from(&mut self) -> Option<usize> {
+        self.changed_from.take()
+    }
+
+    // Attribue ses métadonnées à un objet ajouté pour la première fois ; un objet rétabli
+    // garde les siennes
+    fn stamp(&mut self, object: &mut DrawObject) {
+        self.next_id = self.next_id.max(1);
+        object.stamp(&mut self.next_id, &self.author, now_millis());
+    }
+
+    // Donne aux traits de tout le dessin la couleur de leur entrée de palette ; retourne
+    // les objets modifiés tels qu'ils étaient, avec leur indice
+    fn apply_palette(&mut self) -> Vec<(usize, DrawObject)> {
+        let mut previous = Vec::new();
+        for (index, object) in self.objects.iter_mut().enumerate() {
+            let lines = object.lines();
+            if lines
+                .iter()
+                .any(|line| palette_color(line, &self.palette).is_some())
+            {
+                previous.push((index, object.clone()));
+                follow_palette(&self.palette, object);
+            }
+        }
+        if let Some(&(first, _)) = previous.first() {
+            self.touch(first);
+            let ids = previous.iter().map(|(_, object)| object.meta.id).collect();
+            self.events.emit(DocumentEvent::Recolored(ids));
+        }
+        previous
+    }
+
+    // Note que les objets à partir de `index` ont changé
+    fn touch(&mut self, index: usize) {
+        self.changed_from = Some(self.changed_from.map_or(index, |from| from.min(index)));
+    }
+
+    // Applique `action` sans toucher à l'historique et retourne son inverse,
+    // ou `None` si elle est impossible (indices hors du dessin, insertions au même indice)
+    fn execute(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::Push(mut object) => {
+                self.touch(self.objects.len());
+                self.stamp(&mut object);
+                follow_palette(&self.palette, &mut object);
+                self.events.emit(DocumentEvent::Added(vec![object.meta.id]));
+                self.index.insert(self.objects.len(), object.bounds());
+                self.objects.push(object);
+                Some(Action::Pop)
+            }
+            Action::Pop => {
+                let object = self.objects.pop()?;
+                self.index.remove(self.objects.len(), object.bounds());
+                self.touch(self.objects.len());
+                self.events
+                    .emit(DocumentEvent::Removed(vec![object.meta.id]));
+                Some(Action::Push(object))
+            }
+            Action::Insert(mut items) => {
+                items.sort_by_key(|(index, _)| *index);
+                let valid = items
+                    .iter()
+                    .enumerate()
+                    .all(|(inserted, (index, _))| *index <= self.objects.len() + inserted)
+                    && items.windows(2).all(|pair| pair[0].0 < pair[1].0);
+                if items.is_empty() || !valid {
+                    return None;
+                }
+                self.touch(items[0].0);
+                let indices = items.iter().map(|(index, _)| *index).collect();
+                let mut ids = Vec::with_capacity(items.len());
+                for (index, mut object) in items {
+                    self.stamp(&mut object);
+                    follow_palette(&self.palette, &mut object);
+                    ids.push(object.meta.id);
+                    self.index.shift(index, 1);
+                    self.index.insert(index, object.bounds());
+                    self.objects.insert(index, object);
+                }
+                self.events.emit(DocumentEvent::Added(ids));
+                Some(Action::Remove(indices))
+            }
+            Action::Remove(mut indices) => {
+                indices.sort_unstable();
+                indices.dedup();
+                if indices
+                    .last()
+                    .is_none_or(|&last| last >= self.objects.len())
+                {
+                    return None;
+                }
+                self.touch(indices[0]);
+                let mut items: Vec<(usize, DrawObject)> = indices
+                    .into_iter()
+                    .rev()
+                    .map(|index| {
+                        let object = self.objects.remove(index);
+                        self.index.remove(index, object.bounds());
+                        self.index.shift(index + 1, -1);
+                        (index, object)
+                    })
+                    .collect();
+                items.reverse();
+                let ids = items.iter().map(|(_, object)| object.meta.id).collect();
+                self.events.emit(DocumentEvent::Removed(ids));
+                Some(Action::Insert(items))
+            }
+            Action::Rename(index, name) => {
+                let meta = &mut self.objects.get_mut(index)?.meta;
+                if meta.name == name {
+                    return None;
+                }
+                let previous = std::mem::replace(&mut meta.name, name.clone());
+                let id = meta.id;
+                self.events.emit(DocumentEvent::Renamed(id, name));
+                Some(Action::Rename(index, previous))
+            }
+            Action::Lock(index, locked) => {
+                let meta = &mut self.objects.get_mut(index)?.meta;
+                if meta.locked == locked {
+                    return None;
+                }
+                meta.locked = locked;
+                let id = meta.id;
+                self.events.emit(DocumentEvent::Locked(id, locked));
+                Some(Action::Lock(index, !locked))
+            }
+            Action::SetSwatch(name, color) => {
+                let position = self
+                    .palette
+                    .binary_search_by(|swatch| swatch.name.as_str().cmp(&name));
+                let previous = position.ok().map(|position| self.palette[position].color);
+                match (position, color) {
+                    _ if previous == color => return None,
+                    (Ok(position), Some(color)) => self.palette[position].color = color,
+                    (Ok(position), None) => {
+                        self.palette.remove(position);
+                    }
+                    (Err(position), Some(color)) => self.palette.insert(
+                        position,
+                        Swatch {
+                            name: name.clone(),
+                            color,
+                        },
+                    ),
+                    (Err(_), None) => return None,
+                }
+                self.events
+                    .emit(DocumentEvent::PaletteChanged(name.clone()));
+                let inverse = Action::SetSwatch(name, previous);
+                let recolored = if color.is_some() {
+                    self.apply_palette()
+                } else {
+                    Vec::new()
+                };
+                if recolored.is_empty() {
+                    return Some(inverse);
+                }
+                // Les objets recolorés sont remis tels quels à l'annulation : leurs traits
+                // n'avaient pas forcément la couleur de l'entrée, absente jusque-là
+                let indices = recolored.iter().map(|(index, _)| *index).collect();
+                Some(Action::Batch(vec![
+                    inverse,
+                    Action::Remove(indices),
+                    Action::Insert(recolored),
+                ]))
+            }
+            Action::Batch(actions) => {
+                // Les événements du lot ne partent que s'il est appliqué en entier
+                self.events.hold();
+                let mut inverses = Vec::with_capacity(actions.len());
+                for action in actions {
+                    match self.execute(action) {
+                        Some(inverse) => inverses.push(inverse),
+                        None => {
+                            // Une action est impossible : on défait celles déjà appliquées
+                            for inverse in inverses.into_iter().rev() {
+                                self.execute(inverse);
+                            }
+                            self.events.discard();
+                            return None;
+                        }
+                    }
+                }
+                self.events.release();
+                if inverses.is_empty() {
+                    return None;
+                }
+                inverses.reverse();
+                Some(Action::Batch(inverses))
+            }
+        }
+    }
+
+    /// Dessin au format SVG, limité au format du dessin s'il en a un. Les traits de gomme
+    /// masquent les objets situés dessous, pas le fond propre au document. Les propriétés
+    /// renseignées sont reprises dans `<title>`, `<desc>` et un bloc `<metadata>` Dublin Core.
+    pub fn export_svg(&self) -> String {
+        let bounds = self.page().unwrap_or_else(|| {
+            self.objects.iter().fold(Rect::NOTHING, |bounds, object| {
+                bounds.union(object.bounds())
+            })
+        });
+        let bounds = if bounds.is_positive() {
+            bounds
+        } else {
+            Rect::ZERO
+        };
+        let mut defs = String::new();
+        let mut body = String::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            match &object.figure {
+                Figure::Stroke(line) if line.erase && line.points.len() >= 2 => {
+                    // Masque : tout est visible (blanc) sauf le tracé de la gomme (noir)
+                    defs.push_str(&format!(
+                        "<mask id=\"erase{index}\" maskUnits=\"userSpaceOnUse\">\
+                         <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\
+                         {}</mask>\n",
+                        bounds.min.x,
+                        bounds.min.y,
+                        bounds.width(),
+                        bounds.height(),
+                        svg_line(line, "black"),
+                    ));
+                    body = format!("<g mask=\"url(#erase{index})\">\n{body}</g>\n");
+                }
+                _ => {
+                    body.push_str(&svg_object(object));
+                    body.push('\n');
+                }
+            }
+        }
+        let background = self.properties.background.map_or(String::new(), |color| {
+            format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                 fill-opacity=\"{}\"/>\n",
+                bounds.min.x,
+                bounds.min.y,
+                bounds.width(),
+                bounds.height(),
+                svg_color(color),
+                color.a() as f32 / 255.0,
+            )
+        });
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" \
+             width=\"{}\" height=\"{}\">\n{}<defs>\n{defs}</defs>\n{background}{body}</svg>\n",
+            bounds.min.x,
+            bounds.min.y,
+            bounds.width(),
+            bounds.height(),
+            bounds.width(),
+            bounds.height(),
+            svg_metadata(&self.properties),
+        )
+    }
+}
+
+// Texte échappé pour le contenu ou les attributs d'un élément XML
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Titre, description et métadonnées RDF (Dublin Core, comme Inkscape) d'un document SVG ;
+// vide si aucune propriété n'est renseignée
+fn svg_metadata(properties: &Properties) -> String {
+    let mut out = String::new();
+    if !properties.title.is_empty() {
+        out.push_str(&format!(
+            "<title>{}</title>\n",
+            xml_escape(&properties.title)
+        ));
+    }
+    if !properties.description.is_empty() {
+        out.push_str(&format!(
+            "<desc>{}</desc>\n",
+            xml_escape(&properties.description)
+        ));
+    }
+    let mut fields = String::new();
+    if !properties.title.is_empty() {
+        fields.push_str(&format!(
+            "<dc:title>{}</dc:title>",
+            xml_escape(&properties.title)
+        ));
+    }
+    if !properties.author.is_empty() {
+        fields.push_str(&format!(
+            "<dc:creator><cc:Agent><dc:title>{}</dc:title></cc:Agent></dc:creator>",
+            xml_escape(&properties.author)
+        ));
+    }
+    if !properties.description.is_empty() {
+        fields.push_str(&format!(
+            "<dc:description>{}</dc:description>",
+            xml_escape(&properties.description)
+        ));
+    }
+    if properties.created_at != 0 {
+        fields.push_str(&format!(
+            "<dc:date>{}</dc:date>",
+            iso_8601(properties.created_at)
+        ));
+    }
+    if !fields.is_empty() {
+        out.push_str(&format!(
+            "<metadata><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+             xmlns:cc=\"http://creativecommons.org/ns#\">\
+             <cc:Work rdf:about=\"\">{fields}</cc:Work></rdf:RDF></metadata>\n"
+        ));
+    }
+    out
+}
+
+// Élément SVG d'un objet ; les traits de gomme d'un groupe, qui demanderaient
+// leur propre masque, sont ignorés
+fn svg_object(object: &DrawObject) -> String {
+    match &object.figure {
+        Figure::Stroke(line) if line.erase || line.points.len() < 2 => String::new(),
+        Figure::Stroke(line) => svg_line(line, &svg_color(line.color)),
+        Figure::Rect { rect, outline } => format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            svg_stroke(outline, &svg_color(outline.color)),
+        ),
+        Figure::Ellipse { rect, outline } => format!(
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>",
+            rect.center().x,
+            rect.center().y,
+            rect.width() / 2.0,
+            rect.height() / 2.0,
+            svg_stroke(outline, &svg_color(outline.color)),
+        ),
+        Figure::Text {
+            pos,
+            text,
+            size,
+            outline,
+        } => format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{size}\" dominant-baseline=\"hanging\" \
+             fill=\"{}\" fill-opacity=\"{}\">{}</text>",
+            pos.x,
+            pos.y,
+            svg_color(outline.color),
+            outline.color.a() as f32 / 255.0 * outline.opacity,
+            xml_escape(text),
+        ),
+        Figure::Image { rect, source, .. } => format!(
+            "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" href=\"{}\"/>",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            xml_escape(source),
+        ),
+        Figure::Group(objects) => {
+            let children: Vec<String> = objects.iter().map(svg_object).collect();
+            format!("<g>{}</g>", children.concat())
+        }
+    }
+}
+
+// Couleur SVG, sans transparence : l'opacité est donnée à part
+fn svg_color(color: Color32) -> String {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+// Attributs de contour d'un trait, dans la couleur donnée
+fn svg_stroke(line: &Line, color: &str) -> String {
+    let opacity = line.color.a() as f32 / 255.0 * line.opacity;
+    format!(
+        "fill=\"none\" stroke=\"{color}\" stroke-opacity=\"{opacity}\" stroke-width=\"{}\" \
+         stroke-linecap=\"round\" stroke-linejoin=\"round\"",
+        line.width,
+    )
+}
+
+// Élément SVG d'un trait, dans la couleur donnée
+fn svg_line(line: &Line, color: &str) -> String {
+    let points: Vec<String> = line
+        .points
+        .iter()
+        .map(|point| format!("{},{}", point.x, point.y))
+        .collect();
+    let element = if line.closed { "polygon" } else { "polyline" };
+    format!(
+        "<{element} points=\"{}\" {}/>",
+        points.join(" "),
+        svg_stroke(line, color),
+    )
+}
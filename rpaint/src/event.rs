@@ -0,0 +1,81 @@
+// Diffusion d'événements entre le document et ceux qui le suivent (interface, réseau…).
+// Chaque abonné reçoit sa propre copie des événements, dans l'ordre, et les lit quand il veut :
+// l'émetteur n'a pas à savoir qui écoute.
+
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use serde::{Deserialize, Serialize};
+
+/// Modification du dessin, émise par le document quelle que soit son origine
+/// (outil, script, annulation…). Les objets sont désignés par leur identifiant.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentEvent {
+    /// Objets ajoutés ou rétablis.
+    Added(Vec<u64>),
+    /// Objets retirés.
+    Removed(Vec<u64>),
+    /// Objet renommé ; `None` s'il n'a plus de nom.
+    Renamed(u64, Option<String>),
+    /// Objet verrouillé (`true`) ou déverrouillé.
+    Locked(u64, bool),
+    /// Objets dont les traits ont pris la nouvelle couleur de leur entrée de palette.
+    Recolored(Vec<u64>),
+    /// Entrée de palette créée, modifiée ou retirée, désignée par son nom.
+    PaletteChanged(String),
+}
+
+/// Bus d'événements : chaque événement émis est envoyé à tous les abonnés encore présents.
+pub struct EventBus<E> {
+    subscribers: Vec<Sender<E>>,
+    held: Vec<Vec<E>>, // Événements retenus, par appel à `hold` encore ouvert
+}
+
+impl<E: Clone> EventBus<E> {
+    /// Nouvel abonné : il recevra les événements émis à partir de maintenant.
+    /// Il se désabonne en abandonnant le `Receiver`.
+    pub fn subscribe(&mut self) -> Receiver<E> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Envoie `event` à tous les abonnés et oublie ceux qui ont abandonné leur `Receiver`.
+    /// Entre `hold` et `release`, l'événement est retenu.
+    pub fn emit(&mut self, event: E) {
+        if let Some(held) = self.held.last_mut() {
+            held.push(event);
+            return;
+        }
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Retient les événements émis jusqu'au `release` ou au `discard` correspondant ; les
+    /// appels peuvent s'imbriquer.
+    pub fn hold(&mut self) {
+        self.held.push(Vec::new());
+    }
+
+    /// Émet les événements retenus depuis le dernier `hold`, dans l'ordre ; dans un `hold`
+    /// englobant, ils y restent retenus.
+    pub fn release(&mut self) {
+        for event in self.held.pop().unwrap_or_default() {
+            self.emit(event);
+        }
+    }
+
+    /// Oublie les événements retenus depuis le dernier `hold`.
+    pub fn discard(&mut self) {
+        self.held.pop();
+    }
+}
+
+impl<E> Default for EventBus<E> {
+    fn default() -> Self {
+        Self {
+            subscribers: Vec::new(),
+            held: Vec::new(),
+        }
+    }
+}
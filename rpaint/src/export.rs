@@ -0,0 +1,195 @@
+// Export du dessin en PNG ou en SVG, et fichiers .rpaint du document. Le fichier est écrit
+// dans le dossier de lancement, ou téléchargé par le navigateur dans la version web.
+
+use eframe::egui::{Color32, Rect};
+use rust_paint::document::{self, Document};
+use rust_paint::raster::{self, ExportError};
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Png,
+    Svg,
+    Rpaint,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Svg => "svg",
+            Format::Rpaint => "rpaint",
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn mime_type(self) -> &'static str {
+        match self {
+            Format::Png => "image/png",
+            Format::Svg => "image/svg+xml",
+            Format::Rpaint => "application/json",
+        }
+    }
+}
+
+// Contenu du fichier exporté. Le PNG couvre le format du dessin, à défaut exactement les
+// objets, à un pixel par unité du dessin, sur le fond propre au document s'il en a un,
+// transparent sinon ; les propriétés renseignées y sont écrites en blocs de texte
+pub fn encode(document: &Document, format: Format) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Svg => Ok(document.export_svg().into_bytes()),
+        Format::Rpaint => encode_frames(&[document]),
+        Format::Png => {
+            let area = raster::export_area(&[document]).map_err(export_error)?;
+            render_png(document, area)
+        }
+    }
+}
+
+// Images PNG d'une animation, toutes de la même taille : elles couvrent ensemble les objets
+// de toutes les images, pour rester alignées d'une image à l'autre
+pub fn encode_png_frames(frames: &[&Document]) -> Result<Vec<Vec<u8>>, String> {
+    let area = raster::export_area(frames).map_err(export_error)?;
+    frames
+        .iter()
+        .map(|document| render_png(document, area))
+        .collect()
+}
+
+// Message d'erreur traduit
+fn export_error(error: ExportError) -> String {
+    match error {
+        ExportError::Empty => tr("dessin vide"),
+        ExportError::TooLarge => tr("dessin trop grand"),
+    }
+    .to_owned()
+}
+
+// Image PNG de la zone `bounds` du dessin, de `size` pixels
+fn render_png(document: &Document, (bounds, size): (Rect, [usize; 2])) -> Result<Vec<u8>, String> {
+    let image = raster::render(document, size, bounds.min, 1.0);
+    let background = document.properties().background;
+    let pixels: Vec<u8> = image
+        .pixels
+        .iter()
+        .map(|&pixel| background.map_or(pixel, |background| over(pixel, background)))
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect();
+    encode_png(document, [size[0] as u32, size[1] as u32], &pixels)
+        .map_err(|error| error.to_string())
+}
+
+// Fichier .rpaint : le document seul s'il n'y a qu'une image, sinon la liste des images
+// de l'animation
+pub fn encode_frames(frames: &[&Document]) -> Result<Vec<u8>, String> {
+    match frames {
+        [document] => serde_json::to_vec(document),
+        frames => serde_json::to_vec(frames),
+    }
+    .map_err(|error| error.to_string())
+}
+
+// Images d'un fichier .rpaint, dans l'un ou l'autre format d'`encode_frames`
+pub fn decode_frames(text: &str) -> Result<Vec<Document>, String> {
+    let frames = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_str(text).map(|document| vec![document])
+    };
+    match frames.map_err(|error| error.to_string())? {
+        frames if frames.is_empty() => Err("aucune image".to_owned()),
+        frames => Ok(frames),
+    }
+}
+
+// Pixel `top` posé sur `bottom`, en alpha prémultiplié
+fn over(top: Color32, bottom: Color32) -> Color32 {
+    let keep = 1.0 - top.a() as f32 / 255.0;
+    let channel = |t: u8, b: u8| (t as f32 + b as f32 * keep).round() as u8;
+    Color32::from_rgba_premultiplied(
+        channel(top.r(), bottom.r()),
+        channel(top.g(), bottom.g()),
+        channel(top.b(), bottom.b()),
+        channel(top.a(), bottom.a()),
+    )
+}
+
+// Image PNG RGBA de `size` pixels, avec les propriétés du document sous les mots-clés
+// standard (Title, Author…). Les textes hors Latin-1 vont dans un bloc iTXt, en UTF-8
+fn encode_png(
+    document: &Document,
+    size: [u32; 2],
+    rgba: &[u8],
+) -> Result<Vec<u8>, png::EncodingError> {
+    let properties = document.properties();
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, size[0], size[1]);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let created = (properties.created_at != 0).then(|| document::iso_8601(properties.created_at));
+    let texts = [
+        ("Title", Some(properties.title.clone())),
+        ("Author", Some(properties.author.clone())),
+        ("Description", Some(properties.description.clone())),
+        ("Creation Time", created),
+        ("Software", Some("Rust Paint".to_owned())),
+    ];
+    for (keyword, text) in texts {
+        let Some(text) = text.filter(|text| !text.is_empty()) else {
+            continue;
+        };
+        if text.chars().all(|c| u32::from(c) <= 0xFF) {
+            encoder.add_text_chunk(keyword.to_owned(), text)?;
+        } else {
+            encoder.add_itxt_chunk(keyword.to_owned(), text)?;
+        }
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+    Ok(bytes)
+}
+
+// Écrit `bytes` dans le fichier `name` du dossier de lancement, sans écraser un fichier
+// existant, et retourne le chemin utilisé
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, format: Format, bytes: &[u8]) -> Result<String, String> {
+    let extension = format.extension();
+    let mut path = std::path::PathBuf::from(format!("{name}.{extension}"));
+    let mut copy = 1;
+    while path.exists() {
+        copy += 1;
+        path = format!("{name} ({copy}).{extension}").into();
+    }
+    std::fs::write(&path, bytes).map_err(|error| error.to_string())?;
+    Ok(path.display().to_string())
+}
+
+// Propose `bytes` au téléchargement sous le nom `name`, et retourne ce nom
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, format: Format, bytes: &[u8]) -> Result<String, String> {
+    use wasm_bindgen::JsCast;
+
+    let error = |error: wasm_bindgen::JsValue| format!("{error:?}");
+    let file_name = format!("{name}.{}", format.extension());
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(format.mime_type());
+    let blob =
+        web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+    // Lien temporaire cliqué par programme : le navigateur lance le téléchargement
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("page web introuvable")?
+        .create_element("a")
+        .map_err(error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "lien de téléchargement impossible")?;
+    anchor.set_href(&url);
+    anchor.set_download(&file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(error)?;
+    Ok(file_name)
+}
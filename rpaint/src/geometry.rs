@@ -0,0 +1,169 @@
+// Primitives géométriques du test de contact et de la sélection : distances et
+// intersections entre segments, rectangles, cercles et polygones, et tolérance du pointeur.
+// Tout est en coordonnées du dessin ; les bords comptent comme intérieurs.
+
+use epaint::{Pos2, Rect};
+
+/// Périphérique qui désigne les objets : le doigt est moins précis que la souris
+/// ou le stylet.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InputDevice {
+    /// Souris, pavé tactile ou stylet.
+    #[default]
+    Mouse,
+    /// Doigt sur un écran tactile.
+    Touch,
+}
+
+impl InputDevice {
+    // Marge tolérée autour des traits, en pixels d'écran
+    fn tolerance_pixels(self) -> f32 {
+        match self {
+            InputDevice::Mouse => 4.0,
+            InputDevice::Touch => 12.0,
+        }
+    }
+}
+
+/// Marge tolérée autour des traits par le test de contact, en unités du dessin :
+/// toujours la même à l'écran quel que soit le `zoom` de la vue, et plus large au doigt.
+/// S'ajoute à la demi-épaisseur des traits.
+pub fn hit_tolerance(zoom: f32, device: InputDevice) -> f32 {
+    device.tolerance_pixels() / zoom.max(f32::EPSILON)
+}
+
+// Produit vectoriel 2D : positif si `c` est à gauche de a -> b, nul s'ils sont alignés
+fn cross(a: Pos2, b: Pos2, c: Pos2) -> f32 {
+    (b - a).x * (c - a).y - (b - a).y * (c - a).x
+}
+
+/// Distance entre `pos` et le segment [a, b].
+pub fn distance_to_segment(pos: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    pos.distance(a + ab * t)
+}
+
+/// Vrai si les segments [a, b] et [c, d] se touchent, extrémités et segments alignés compris.
+pub fn segments_intersect(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> bool {
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    // Cas limites : une extrémité posée sur l'autre segment
+    let on_segment = |p: Pos2, q: Pos2, r: Pos2| Rect::from_two_pos(p, q).contains(r);
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
+/// Plus petite distance entre les segments [a, b] et [c, d] ; nulle s'ils se touchent.
+pub fn distance_between_segments(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> f32 {
+    if segments_intersect(a, b, c, d) {
+        return 0.0;
+    }
+    // Sans croisement, la distance est atteinte à une des quatre extrémités
+    distance_to_segment(a, c, d)
+        .min(distance_to_segment(b, c, d))
+        .min(distance_to_segment(c, a, b))
+        .min(distance_to_segment(d, a, b))
+}
+
+// Côtés du polygone fermé `polygon`, le dernier point étant relié au premier
+fn edges(polygon: &[Pos2]) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+    polygon
+        .last()
+        .into_iter()
+        .chain(polygon)
+        .copied()
+        .zip(polygon.iter().copied())
+}
+
+/// Vrai si le segment [a, b] touche `rect`, même sans avoir d'extrémité à l'intérieur.
+pub fn segment_intersects_rect(a: Pos2, b: Pos2, rect: Rect) -> bool {
+    if !rect.is_finite() || rect.min.x > rect.max.x || rect.min.y > rect.max.y {
+        return false;
+    }
+    if rect.contains(a) || rect.contains(b) {
+        return true;
+    }
+    if !Rect::from_two_pos(a, b).intersects(rect) {
+        return false;
+    }
+    let corners = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    edges(&corners).any(|(c, d)| segments_intersect(a, b, c, d))
+}
+
+/// Plus petite distance entre le segment [a, b] et `rect` ; nulle s'ils se touchent.
+pub fn distance_segment_to_rect(a: Pos2, b: Pos2, rect: Rect) -> f32 {
+    if segment_intersects_rect(a, b, rect) {
+        return 0.0;
+    }
+    let corners = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    edges(&corners)
+        .map(|(c, d)| distance_between_segments(a, b, c, d))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Vrai si le segment [a, b] touche le disque de centre `center` et de rayon `radius`.
+pub fn segment_intersects_circle(a: Pos2, b: Pos2, center: Pos2, radius: f32) -> bool {
+    distance_to_segment(center, a, b) <= radius
+}
+
+/// Vrai si `pos` est à l'intérieur du polygone fermé `polygon` (règle pair-impair :
+/// les zones recouvertes deux fois par un lasso croisé sont extérieures).
+pub fn polygon_contains(polygon: &[Pos2], pos: Pos2) -> bool {
+    let Some(&last) = polygon.last() else {
+        return false;
+    };
+    let mut inside = false;
+    let mut previous = last;
+    for &point in polygon {
+        // Arête qui traverse l'horizontale de `pos`, à droite de `pos`
+        if (point.y > pos.y) != (previous.y > pos.y) {
+            let x = point.x + (pos.y - point.y) * (previous.x - point.x) / (previous.y - point.y);
+            if pos.x < x {
+                inside = !inside;
+            }
+        }
+        previous = point;
+    }
+    inside
+}
+
+/// Vrai si le segment [a, b] touche le polygone fermé `polygon` : une extrémité à
+/// l'intérieur, ou un croisement avec un de ses côtés.
+pub fn segment_intersects_polygon(a: Pos2, b: Pos2, polygon: &[Pos2]) -> bool {
+    polygon_contains(polygon, a)
+        || polygon_contains(polygon, b)
+        || edges(polygon).any(|(c, d)| segments_intersect(a, b, c, d))
+}
+
+/// Plus petite distance entre le segment [a, b] et le polygone fermé `polygon` ;
+/// nulle s'ils se touchent, infinie si le polygone est vide.
+pub fn distance_segment_to_polygon(a: Pos2, b: Pos2, polygon: &[Pos2]) -> f32 {
+    if polygon_contains(polygon, a) || polygon_contains(polygon, b) {
+        return 0.0;
+    }
+    edges(polygon)
+        .map(|(c, d)| distance_between_segments(a, b, c, d))
+        .fold(f32::INFINITY, f32::min)
+}
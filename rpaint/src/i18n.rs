@@ -0,0 +1,320 @@
+// Traduction de l'interface.
+// Les textes sont écrits en français dans le code et servent de clé : chaque langue
+// fournit sa table de correspondance, et un texte absent de la table reste en français.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Langue de l'interface.
+#[derive(Clone, Copy, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Language {
+    French,
+    English,
+}
+
+// Langue active, partagée par tous les appels à `tr`
+static CURRENT: AtomicU8 = AtomicU8::new(Language::French as u8);
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::French, Language::English];
+
+    /// Nom de la langue, dans cette langue.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::French => "Français",
+            Language::English => "English",
+        }
+    }
+
+    /// Langue du système : le français si la locale l'indique, l'anglais sinon.
+    pub fn detect() -> Self {
+        match sys_locale::get_locale() {
+            Some(locale) if !locale.to_lowercase().starts_with("fr") => Language::English,
+            _ => Language::French,
+        }
+    }
+
+    /// Traduit `text` (écrit en français) dans cette langue.
+    pub fn tr(self, text: &'static str) -> &'static str {
+        match self {
+            Language::French => text,
+            Language::English => english(text).unwrap_or(text),
+        }
+    }
+}
+
+/// Change la langue active.
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+/// Langue active.
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        0 => Language::French,
+        _ => Language::English,
+    }
+}
+
+/// Traduit `text` dans la langue active.
+pub fn tr(text: &'static str) -> &'static str {
+    language().tr(text)
+}
+
+/// Traduit le modèle `template` puis remplace ses `{}` par `args`, dans l'ordre.
+pub fn tr_fill(template: &'static str, args: &[&str]) -> String {
+    let mut parts = tr(template).split("{}");
+    let mut result = parts.next().unwrap_or_default().to_owned();
+    for (part, arg) in parts.zip(args.iter().chain(std::iter::repeat(&""))) {
+        result.push_str(arg);
+        result.push_str(part);
+    }
+    result
+}
+
+fn english(text: &str) -> Option<&'static str> {
+    Some(match text {
+        // Outils
+        "✏ Outils" => "✏ Tools",
+        "Main levée" => "Freehand",
+        "Ligne" => "Line",
+        "Gomme" => "Eraser",
+        "✏ Main levée" => "✏ Freehand",
+        "📏 Ligne" => "📏 Line",
+        "🧽 Gomme" => "🧽 Eraser",
+        "Pointeur laser" => "Laser pointer",
+        "🔦 Pointeur laser" => "🔦 Laser pointer",
+        "Taille" => "Size",
+        "Opacité" => "Opacity",
+        "Mode Gomme actif" => "Eraser mode active",
+        "⌨ Dessin au clavier" => "⌨ Keyboard drawing",
+        "Flèches : déplacer (Maj : plus vite)\n\
+         Entrée : poser / lever le crayon\n\
+         Échap : abandonner le trait" => {
+            "Arrows: move (Shift: faster)\nEnter: pen down / up\nEscape: discard the stroke"
+        }
+        // Couleur
+        "🎨 Couleur" => "🎨 Colour",
+        "Couleur {}" => "Colour {}",
+        "Couleur du pinceau" => "Brush colour",
+        "Couleur hexadécimale" => "Hex colour",
+        "TSV" => "HSV",
+        "Teinte" => "Hue",
+        "Saturation" => "Saturation",
+        "Valeur" => "Value",
+        "Clic droit pour retirer" => "Right-click to remove",
+        "Ajouter la couleur actuelle à la palette" => "Add the current colour to the palette",
+        "Ajouter à la palette" => "Add to palette",
+        "Couleurs nommées" => "Named colors",
+        "Donner la couleur actuelle à tous ses traits" => {
+            "Give the current color to all its strokes"
+        }
+        "Redéfinir {}" => "Redefine {}",
+        "Supprimer la couleur {}" => "Delete color {}",
+        "Nommer la couleur actuelle" => "Name the current color",
+        "Récentes" => "Recent",
+        // Préréglages
+        "⭐ Préréglages" => "⭐ Presets",
+        "Marqueur rouge" => "Red marker",
+        "Crayon fin" => "Fine pencil",
+        "Touche {}" => "Key {}",
+        "Supprimer" => "Delete",
+        "Supprimer le préréglage {}" => "Delete preset {}",
+        "Nom" => "Name",
+        "Verrouillé" => "Locked",
+        "💾 Enregistrer" => "💾 Save",
+        // Historique
+        "↩ Historique" => "↩ History",
+        "↩ Annuler" => "↩ Undo",
+        "↪ Rétablir" => "↪ Redo",
+        "🗑 Vider l'historique" => "🗑 Clear history",
+        "🗑 Vider le rétablissement" => "🗑 Clear redo",
+        "🗑 Effacer tout" => "🗑 Clear all",
+        // Export
+        "📤 Exporter" => "📤 Export",
+        "📤 Exporter en PNG" => "📤 Export as PNG",
+        "📤 Exporter en SVG" => "📤 Export as SVG",
+        "Dessin exporté : {}" => "Drawing exported: {}",
+        "🎞 Exporter l'animation en PNG" => "🎞 Export animation as PNG",
+        "Animation exportée : {} images ({}…)" => "Animation exported: {} images ({}…)",
+        "Export impossible : {}" => "Export failed: {}",
+        "dessin vide" => "empty drawing",
+        "dessin trop grand" => "drawing too large",
+        "Document enregistré : {}" => "Document saved: {}",
+        "Enregistrement impossible : {}" => "Save failed: {}",
+        // Propriétés du document
+        "📝 Propriétés du document…" => "📝 Document properties…",
+        "📝 Propriétés du document" => "📝 Document properties",
+        "Titre" => "Title",
+        "Description" => "Description",
+        "Créé" => "Created",
+        "Modifié" => "Modified",
+        "inconnue" => "unknown",
+        "Fond propre au document" => "Document background",
+        "Format fixe" => "Fixed size",
+        "Largeur" => "Width",
+        "Hauteur" => "Height",
+        "Appliquer" => "Apply",
+        // Animation
+        "🎞 Animation" => "🎞 Animation",
+        "Image {} / {}" => "Frame {} / {}",
+        "Pause" => "Pause",
+        "Lecture" => "Play",
+        "images/s" => "frames/s",
+        "◀ Image précédente" => "◀ Previous frame",
+        "▶ Image suivante" => "▶ Next frame",
+        "➕ Nouvelle image" => "➕ New frame",
+        "⎘ Dupliquer l'image" => "⎘ Duplicate frame",
+        "🗑 Supprimer l'image" => "🗑 Delete frame",
+        "⏵ Lecture / pause" => "⏵ Play / pause",
+        // Apparence
+        "🖌 Apparence" => "🖌 Appearance",
+        "☀ Clair" => "☀ Light",
+        "🌙 Sombre" => "🌙 Dark",
+        "◑ Contraste élevé" => "◑ High contrast",
+        "Panneau à droite (gaucher)" => "Panel on the right (left-handed)",
+        "Langue" => "Language",
+        "Échelle de l'interface" => "UI scale",
+        "Fond du canevas" => "Canvas background",
+        "Fond personnalisé" => "Custom background",
+        "Sombre" => "Dark",
+        "Ardoise" => "Slate",
+        "Noir" => "Black",
+        "Papier" => "Paper",
+        "Blanc" => "White",
+        // Raccourcis
+        "⌨ Raccourcis" => "⌨ Shortcuts",
+        "Appuyez sur une touche…" => "Press a key…",
+        "Changer le raccourci" => "Change the shortcut",
+        "Retirer le raccourci" => "Remove the shortcut",
+        "Raccourci par défaut" => "Default shortcut",
+        // Référence
+        "🖼 Référence" => "🖼 Reference",
+        "Afficher (R)" => "Show (R)",
+        "Au-dessus des traits" => "Above the strokes",
+        "🔒 Verrouiller comme fond" => "🔒 Lock as background",
+        "Sinon, Ctrl + glisser pour la déplacer" => "Otherwise, Ctrl + drag to move it",
+        "Retirer l'image" => "Remove image",
+        "Déposez une image PNG ou JPEG sur la fenêtre." => {
+            "Drop a PNG or JPEG image onto the window."
+        }
+        "Image illisible : {}" => "Unreadable image: {}",
+        // Script
+        "📜 Script" => "📜 Script",
+        "▶ Exécuter" => "▶ Run",
+        "Aucun script" => "No scripts",
+        "Script « {} » illisible : {}" => "Unreadable script \"{}\": {}",
+        "Commande indisponible : {}" => "Unavailable command: {}",
+        "Scripts du dossier « {} »" => "Scripts in the \"{}\" folder",
+        // Inspecteur
+        "🔎 Inspecteur" => "🔎 Inspector",
+        "Aucun objet" => "No objects",
+        "Trait" => "Stroke",
+        "Texte" => "Text",
+        "Image" => "Image",
+        "Groupe" => "Group",
+        "Identifiant" => "ID",
+        "Type" => "Type",
+        "Auteur" => "Author",
+        "Création" => "Created",
+        "à l'instant" => "just now",
+        "il y a {} min" => "{} min ago",
+        "il y a {} h" => "{} h ago",
+        "il y a {} j" => "{} d ago",
+        // Statistiques
+        "📊 Statistiques" => "📊 Statistics",
+        "Objets" => "Objects",
+        "{} (dont {} de gomme)" => "{} ({} eraser)",
+        "Points" => "Points",
+        "Emprise" => "Bounds",
+        "Fichier" => "File",
+        "Taille estimée à l'enregistrement, toutes images comprises" => {
+            "Estimated size once saved, all frames included"
+        }
+        "Objets par couleur" => "Objects per colour",
+        "Autres couleurs : {}" => "Other colours: {}",
+        // Performances
+        "⚙ Performances" => "⚙ Performance",
+        " Mo" => " MB",
+        "Budget mémoire" => "Memory budget",
+        "Profileur" => "Profiler",
+        "Débogage" => "Debugging",
+        "Nombre de traits" => "Number of strokes",
+        "Générer des traits" => "Generate strokes",
+        "Image         : {} ms" => "Frame         : {} ms",
+        "Rastérisation : {} ms ({} triangles)" => "Rasterization : {} ms ({} triangles)",
+        "Formes        : {}" => "Shapes        : {}",
+        "Contact       : {} ms" => "Hit test      : {} ms",
+        "Traits        : {}" => "Strokes       : {}",
+        // Journal
+        "Journal" => "Log",
+        "📋 Journal" => "📋 Log",
+        "Erreurs" => "Errors",
+        "Avertissements" => "Warnings",
+        "Informations" => "Info",
+        "Filtrer" => "Filter",
+        "Effacer" => "Clear",
+        // Fenêtre principale
+        "Afficher le panneau" => "Show panel",
+        "Masquer le panneau" => "Hide panel",
+        "Mémoire : {} (dessin {}, historique {})" => "Memory: {} (drawing {}, history {})",
+        "⚠ Budget mémoire dépassé" => "⚠ Memory budget exceeded",
+        "Vider l'historique" => "Clear history",
+        "Oublie les modifications de toutes les images, qui ne pourront \
+         plus être annulées ni rétablies" => {
+            "Forgets the changes of every frame, which can no longer be undone or redone"
+        }
+        "Zone de dessin" => "Drawing area",
+        "🗐 Copier" => "🗐 Copy",
+        "⬆ Premier plan" => "⬆ Bring to front",
+        "🎨 Appliquer la couleur du pinceau" => "🎨 Apply the brush colour",
+        "🔒 Verrouiller" => "🔒 Lock",
+        "🔓 Déverrouiller" => "🔓 Unlock",
+        "🗑 Supprimer l'objet" => "🗑 Delete object",
+        "📋 Coller ici" => "📋 Paste here",
+        "Niveaux de zoom" => "Zoom levels",
+        "⛶ Afficher l'interface" => "⛶ Show the interface",
+        "+ Zoom avant" => "+ Zoom in",
+        "− Zoom arrière" => "− Zoom out",
+        "⛶ Plein écran sans panneaux" => "⛶ Full screen without panels",
+        "📽 Présenter le dessin étape par étape" => "📽 Present the drawing step by step",
+        "🖼 Afficher l'image de référence" => "🖼 Show the reference image",
+        "⏱ Profileur" => "⏱ Profiler",
+        // Présentation
+        "Étape {} / {}" => "Step {} / {}",
+        "→ : étape suivante, ← : étape précédente\n\
+         Début / Fin : tout masquer / tout révéler" => {
+            "→: next step, ←: previous step\nHome / End: hide all / reveal all"
+        }
+        "Quitter la présentation" => "Exit presentation",
+        "Échap" => "Escape",
+        // Confirmations
+        "Effacer tout ?" => "Clear everything?",
+        "Les {} traits du dessin seront supprimés (Ctrl+Z pour annuler)." => {
+            "The drawing's {} strokes will be deleted (Ctrl+Z to undo)."
+        }
+        "Ne plus demander" => "Don't ask again",
+        "Annuler" => "Cancel",
+        "Quitter sans enregistrer ?" => "Quit without saving?",
+        "Le dessin en cours sera perdu." => "The current drawing will be lost.",
+        "💾 Enregistrer et quitter" => "💾 Save and quit",
+        "Quitter" => "Quit",
+        "sans-titre" => "untitled",
+        "Document « {} » illisible : {}" => "Unreadable document \"{}\": {}",
+        // Notifications
+        "Réessayer" => "Retry",
+        "Fermer" => "Close",
+        "Extension ignorée : {}" => "Plugin skipped: {}",
+        // Écran d'accueil
+        "Bienvenue dans Rust Paint" => "Welcome to Rust Paint",
+        "Nouveau document" => "New document",
+        "✏ Commencer à dessiner" => "✏ Start drawing",
+        "Documents récents" => "Recent documents",
+        "Afficher au démarrage" => "Show at startup",
+        // Unités de taille mémoire
+        "o" => "B",
+        "Ko" => "KB",
+        "Mo" => "MB",
+        "Go" => "GB",
+        _ => return None,
+    })
+}
@@ -0,0 +1,20 @@
+//! Moteur de document de Rust Paint, sans interface graphique : modèle du dessin,
+//! test de contact et géométrie, rastérisation logicielle et scripts.
+//! Ne dépend que d'`epaint`, pour les types géométriques et la tessellation,
+//! de `serde` pour la sérialisation du modèle, de `rhai` pour les scripts et de `tracing`
+//! pour le journal. `web-time` fournit l'horloge, y compris dans le navigateur.
+//! La fonctionnalité `widget` ajoute [`canvas::PaintCanvas`], une zone de dessin à intégrer
+//! dans d'autres applications egui.
+
+#[cfg(feature = "widget")]
+pub mod canvas;
+pub mod color;
+pub mod document;
+pub mod event;
+pub mod geometry;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+mod quadtree;
+pub mod raster;
+pub mod script;
+pub mod tool;
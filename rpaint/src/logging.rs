@@ -0,0 +1,161 @@
+// Journal de l'application : les événements `tracing` sont gardés en mémoire pour la console
+// du journal, les avertissements et erreurs sont écrits sur la sortie d'erreur, et tout est
+// copié dans le fichier `RPAINT_LOG_FILE` si cette variable est définie.
+// La variable `RUST_LOG` remplace le filtre par défaut (syntaxe d'`EnvFilter`).
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::sync::{Mutex, OnceLock};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::layer;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use web_time::Instant;
+
+// Nombre d'entrées gardées pour la console ; les plus anciennes sont oubliées
+const CAPACITY: usize = 2000;
+
+// Filtre utilisé sans `RUST_LOG` : détails de l'application, seulement l'essentiel d'egui
+const DEFAULT_FILTER: &str = "info,rust_paint=debug";
+
+// Variable d'environnement donnant le fichier du journal
+const FILE_VAR: &str = "RPAINT_LOG_FILE";
+
+pub struct Entry {
+    pub time: f64, // Secondes depuis le démarrage
+    pub level: Level,
+    pub target: &'static str,
+    pub text: String, // Spans englobants, puis message et champs
+}
+
+static ENTRIES: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+// Installe le journal ; à appeler une seule fois, au démarrage
+pub fn init() {
+    START.get_or_init(Instant::now);
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    // L'échec d'ouverture du fichier n'est signalé qu'une fois le journal installé
+    let mut file_error = None;
+    let file_layer = std::env::var_os(FILE_VAR).and_then(|path| match File::create(&path) {
+        Ok(file) => Some(layer().with_ansi(false).with_writer(Mutex::new(file))),
+        Err(error) => {
+            file_error = Some((path, error));
+            None
+        }
+    });
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(ConsoleLayer)
+        .with(
+            layer()
+                .with_writer(std::io::stderr)
+                .with_filter(LevelFilter::WARN),
+        )
+        .with(file_layer)
+        .init();
+    if let Some((path, error)) = file_error {
+        tracing::warn!(path = %path.to_string_lossy(), %error, "fichier du journal inaccessible");
+    }
+}
+
+// Appelle `f` avec les entrées gardées, de la plus ancienne à la plus récente
+pub fn with_entries<R>(f: impl FnOnce(&VecDeque<Entry>) -> R) -> R {
+    f(&ENTRIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+pub fn clear() {
+    ENTRIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clear();
+}
+
+// Message et champs d'un événement ou d'un span, mis en forme
+#[derive(Default)]
+struct Fields {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+// Champs d'un span, gardés dans ses extensions pour préfixer les événements qu'il englobe
+struct SpanFields(String);
+
+// Couche `tracing` qui alimente la console du journal
+struct ConsoleLayer;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ConsoleLayer {
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut text = String::new();
+        for span in ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            text.push_str(span.name());
+            if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>()
+                && !fields.is_empty()
+            {
+                let _ = write!(text, "{{{}}}", fields.trim_start());
+            }
+            text.push_str(" : ");
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        text.push_str(&fields.message);
+        text.push_str(&fields.fields);
+
+        let metadata = event.metadata();
+        let entry = Entry {
+            time: START
+                .get()
+                .map_or(0.0, |start| start.elapsed().as_secs_f64()),
+            level: *metadata.level(),
+            target: metadata.target(),
+            text,
+        };
+        let mut entries = ENTRIES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if entries.len() == CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
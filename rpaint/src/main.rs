@@ -1,174 +1,3160 @@
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
 use eframe::egui;
-use egui::{Color32, Pos2, Stroke};
+use egui::ecolor::Hsva;
+use egui::epaint::{PathShape, Tessellator};
+use egui::{Color32, ColorImage, Mesh, Pos2, Rect, Stroke, TextureHandle, TextureOptions, Vec2};
+
+mod cli;
+mod commands;
+mod export;
+mod i18n;
+mod logging;
+mod toast;
+
+use i18n::{Language, tr, tr_fill};
+use rust_paint::color::{color_to_hex, parse_hex_color};
+use rust_paint::document::{Action, Document, DrawObject, Figure, Line, Properties};
+use rust_paint::event::DocumentEvent;
+use rust_paint::geometry::{self, InputDevice};
+use rust_paint::raster;
+use rust_paint::script;
+use rust_paint::tool::{Brush, Freehand, Outline, PointerEvent, StraightLine, Tool};
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Nom de l'application, à la fin du titre de la fenêtre
+const APP_NAME: &str = "Rust Paint Pro";
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions::default();
+    let args = <cli::Args as clap::Parser>::parse();
+    logging::init();
+    let mut options = eframe::NativeOptions::default();
+    if let Some(size) = args.window_size {
+        options.viewport = options.viewport.with_inner_size(size);
+    }
     eframe::run_native(
-        "Rust Paint Pro",
+        APP_NAME,
         options,
-        Box::new(|_cc| Box::new(PaintApp::default())),
+        Box::new(|cc| Box::new(PaintApp::new(cc, args))),
     )
 }
 
-#[derive(Clone, PartialEq)]
+// Version web : pas de ligne de commande, l'application occupe le canevas `CANVAS_ID`
+// de `index.html` et ses préférences sont gardées dans le stockage local du navigateur
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    logging::init();
+    wasm_bindgen_futures::spawn_local(async {
+        let started = eframe::WebRunner::new()
+            .start(
+                CANVAS_ID,
+                eframe::WebOptions::default(),
+                Box::new(|cc| Box::new(PaintApp::new(cc, cli::Args::default()))),
+            )
+            .await;
+        if let Err(error) = started {
+            tracing::error!(?error, "démarrage impossible");
+        }
+    });
+}
+
+// Identifiant du canevas de la page web
+#[cfg(target_arch = "wasm32")]
+const CANVAS_ID: &str = "rpaint_canvas";
+
+// Dossier des extensions chargées au démarrage (fonctionnalité `plugins`)
+#[cfg(feature = "plugins")]
+const PLUGINS_DIR: &str = "plugins";
+
+// Dossier des scripts proposés par la console, relatif au dossier de lancement
+const SCRIPTS_DIR: &str = "scripts";
+
+// Clé des préférences dans le stockage d'eframe
+const PREFERENCES_KEY: &str = "preferences";
+
+// Au-delà de ce nombre de traits, « Effacer tout » demande confirmation
+const CONFIRM_CLEAR_LINES: usize = 50;
+
+// Zoom du canevas : limites et niveaux proposés dans la barre d'état
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.1..=10.0;
+const ZOOM_LEVELS: [f32; 8] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0, 8.0];
+
+// Pointeur laser : couleur, épaisseur à l'écran, durée d'affichage puis d'effacement (s)
+const LASER_COLOR: Color32 = Color32::from_rgb(255, 40, 40);
+const LASER_WIDTH: f32 = 4.0;
+const LASER_HOLD: f32 = 1.0;
+const LASER_FADE: f32 = 1.0;
+
+// Nombre de couleurs récentes conservées
+const RECENT_COLORS: usize = 10;
+
+// Nombre de documents récents proposés par l'écran d'accueil
+const RECENT_FILES: usize = 8;
+
+// Format proposé quand le dessin en prend un, et limites de ses côtés
+const DEFAULT_PAGE_SIZE: Vec2 = Vec2::new(1920.0, 1080.0);
+const PAGE_SIDE_RANGE: std::ops::RangeInclusive<f32> = 1.0..=16384.0;
+
+// Touches associées aux premiers préréglages
+const PRESET_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+// Palette proposée par défaut sous le sélecteur de couleur
+const DEFAULT_SWATCHES: [Color32; 12] = [
+    Color32::BLACK,
+    Color32::WHITE,
+    Color32::GRAY,
+    Color32::RED,
+    Color32::from_rgb(255, 140, 0),
+    Color32::YELLOW,
+    Color32::GREEN,
+    Color32::DARK_GREEN,
+    Color32::LIGHT_BLUE,
+    Color32::BLUE,
+    Color32::from_rgb(150, 60, 200),
+    Color32::BROWN,
+];
+
+// Fonds de canevas proposés
+const CANVAS_BACKGROUNDS: [(&str, Color32); 5] = [
+    ("Sombre", Color32::from_gray(27)),
+    ("Ardoise", Color32::from_rgb(40, 44, 52)),
+    ("Noir", Color32::BLACK),
+    ("Papier", Color32::from_rgb(250, 246, 235)),
+    ("Blanc", Color32::WHITE),
+];
+
+// Thème de l'interface
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Theme {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Light => egui::Visuals::light(),
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::HighContrast => high_contrast_visuals(),
+        }
+    }
+}
+
+// Thème sombre à contraste renforcé : fonds noirs, textes blancs, contours jaunes
+fn high_contrast_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(20);
+    visuals.hyperlink_color = Color32::from_rgb(0, 255, 255);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 70, 160);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::YELLOW);
+    visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    for widget in [
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+    ] {
+        widget.bg_fill = Color32::BLACK;
+        widget.weak_bg_fill = Color32::BLACK;
+        widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    visuals.widgets.active.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    visuals
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 enum BrushMode {
     Freehand,
     StraightLine,
+    Rectangle,
+    Ellipse,
     Eraser,
+    Laser,          // Pointeur de présentation : traits éphémères, hors du document
+    Plugin(String), // Outil d'une extension, désigné par son nom
+}
+
+impl BrushMode {
+    const ALL: [BrushMode; 6] = [
+        BrushMode::Freehand,
+        BrushMode::StraightLine,
+        BrushMode::Rectangle,
+        BrushMode::Ellipse,
+        BrushMode::Eraser,
+        BrushMode::Laser,
+    ];
+
+    // Commande sélectionnant l'outil ; les outils des extensions n'en ont pas
+    fn command(&self) -> Option<&'static commands::Command> {
+        commands::find(match self {
+            BrushMode::Freehand => "tool.freehand",
+            BrushMode::StraightLine => "tool.line",
+            BrushMode::Rectangle => "tool.rectangle",
+            BrushMode::Ellipse => "tool.ellipse",
+            BrushMode::Eraser => "tool.eraser",
+            BrushMode::Laser => "tool.laser",
+            BrushMode::Plugin(_) => return None,
+        })
+    }
+}
+
+// Trait du pointeur laser (points de `path`, coordonnées de l'écran) : halo large et
+// transparent, puis cœur, tessellés dans `out` à partir des mêmes points
+fn draw_laser(tessellator: &mut Tessellator, path: &mut PathShape, alpha: f32, out: &mut Mesh) {
+    path.stroke = Stroke::new(LASER_WIDTH * 3.0, LASER_COLOR.gamma_multiply(0.25 * alpha));
+    tessellator.tessellate_path(path, out);
+    path.stroke = Stroke::new(LASER_WIDTH, LASER_COLOR.gamma_multiply(alpha));
+    tessellator.tessellate_path(path, out);
+}
+
+// Petit carré de couleur cliquable, entouré lorsqu'il correspond à la couleur active
+fn color_swatch(ui: &mut egui::Ui, color: Color32, selected: bool) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::click());
+    let stroke = if selected || response.hovered() {
+        ui.visuals().selection.stroke
+    } else {
+        ui.visuals().widgets.noninteractive.bg_stroke
+    };
+    ui.painter().rect(rect, 2.0, color, stroke);
+    response.widget_info(|| {
+        let label = tr_fill("Couleur {}", &[&color_to_hex(color)]);
+        egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, selected, label)
+    });
+    response
+}
+
+// Choix du fond du canevas : fonds proposés, puis couleur libre
+fn background_picker(ui: &mut egui::Ui, background: &mut Color32) {
+    ui.horizontal_wrapped(|ui| {
+        for (name, color) in CANVAS_BACKGROUNDS {
+            if color_swatch(ui, color, *background == color)
+                .on_hover_text(tr(name))
+                .clicked()
+            {
+                *background = color;
+            }
+        }
+        let button = ui.color_edit_button_srgba(background);
+        accessible_label(
+            &button,
+            egui::WidgetType::ColorButton,
+            tr("Fond personnalisé"),
+        );
+    });
+}
+
+// Format du dessin : aucun (canevas sans limites), ou largeur × hauteur
+fn size_picker(ui: &mut egui::Ui, size: &mut Option<Vec2>) {
+    ui.horizontal(|ui| {
+        let mut fixed = size.is_some();
+        ui.checkbox(&mut fixed, tr("Format fixe"));
+        match (fixed, size) {
+            (true, Some(size)) => {
+                let width = ui.add(egui::DragValue::new(&mut size.x).clamp_range(PAGE_SIDE_RANGE));
+                accessible_label(&width, egui::WidgetType::DragValue, tr("Largeur"));
+                ui.label("×");
+                let height = ui.add(egui::DragValue::new(&mut size.y).clamp_range(PAGE_SIDE_RANGE));
+                accessible_label(&height, egui::WidgetType::DragValue, tr("Hauteur"));
+            }
+            (true, size) => *size = Some(DEFAULT_PAGE_SIZE),
+            (false, size) => *size = None,
+        }
+    });
+}
+
+// Bouton-icône : le nom complet sert d'infobulle et de libellé pour les lecteurs d'écran
+fn icon_button(ui: &mut egui::Ui, icon: &str, label: &str) -> egui::Response {
+    let response = ui.button(icon).on_hover_text(label);
+    accessible_label(&response, egui::WidgetType::Button, label);
+    response
 }
 
-struct Line {
-    points: Vec<Pos2>,
+// Donne un libellé accessible à un widget qui n'affiche pas de texte explicite
+fn accessible_label(response: &egui::Response, typ: egui::WidgetType, label: &str) {
+    response.widget_info(|| egui::WidgetInfo::labeled(typ, label));
+}
+
+// Affiche l'âge d'un objet créé à `created_at` (millisecondes depuis l'époque Unix)
+fn format_age(created_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let minutes = now.saturating_sub(created_at) / 60_000;
+    match minutes {
+        0 => tr("à l'instant").to_owned(),
+        1..60 => tr_fill("il y a {} min", &[&minutes.to_string()]),
+        60..1440 => tr_fill("il y a {} h", &[&(minutes / 60).to_string()]),
+        _ => tr_fill("il y a {} j", &[&(minutes / 1440).to_string()]),
+    }
+}
+
+// Nom de l'utilisateur du système, auteur des objets dessinés
+fn user_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+// Affiche une taille en octets de façon lisible (« 1.5 Mo »)
+fn format_bytes(bytes: usize) -> String {
+    let units = [tr("o"), tr("Ko"), tr("Mo"), tr("Go")];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} {}", units[0])
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+// Combinaison d'outil, de couleur, de taille et d'opacité enregistrée sous un nom
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ToolPreset {
+    name: String,
+    mode: BrushMode,
     color: Color32,
-    width: f32,
+    size: f32,
+    #[serde(default = "opaque")]
+    opacity: f32,
+}
+
+// Opacité des préréglages enregistrés avant qu'ils ne la retiennent
+fn opaque() -> f32 {
+    1.0
+}
+
+// Préférences conservées d'une session à l'autre
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Preferences {
+    presets: Vec<ToolPreset>,
+    swatches: Vec<Color32>, // Couleurs ajoutées par l'utilisateur à la palette
+    theme: Theme,
+    canvas_background: Color32,
+    language: Language,
+    ui_scale: f32,       // Zoom de l'interface, appliqué à l'échelle native de l'écran
+    show_welcome: bool,  // Écran d'accueil au démarrage
+    confirm_clear: bool, // Confirmation avant d'effacer un dessin important
+    panel_right: bool,   // Disposition pour gaucher : panneau de réglages à droite
+    memory_budget_mb: f32, // Seuil au-delà duquel la barre d'état affiche un avertissement
+    recent_files: Vec<std::path::PathBuf>, // Documents ouverts ou enregistrés, le plus récent en premier
+    shortcuts: commands::Shortcuts,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        let language = Language::detect();
+        Self {
+            presets: vec![
+                ToolPreset {
+                    name: language.tr("Marqueur rouge").to_owned(),
+                    mode: BrushMode::Freehand,
+                    color: Color32::RED,
+                    size: 12.0,
+                    opacity: 1.0,
+                },
+                ToolPreset {
+                    name: language.tr("Crayon fin").to_owned(),
+                    mode: BrushMode::Freehand,
+                    color: Color32::GRAY,
+                    size: 1.5,
+                    opacity: 1.0,
+                },
+            ],
+            swatches: Vec::new(),
+            theme: Theme::Dark,
+            canvas_background: CANVAS_BACKGROUNDS[0].1,
+            language,
+            ui_scale: 1.0,
+            show_welcome: true,
+            confirm_clear: true,
+            panel_right: false,
+            memory_budget_mb: 256.0,
+            recent_files: Vec::new(),
+            shortcuts: commands::default_shortcuts(),
+        }
+    }
+}
+
+// Image de référence affichée avec le dessin, sans en faire partie
+struct Reference {
+    texture: TextureHandle,
+    rect: Rect, // Position et taille, en coordonnées du dessin
+    opacity: f32,
+    above: bool, // Au-dessus des traits plutôt qu'en dessous
+    visible: bool,
+    locked: bool, // Fixée comme fond : ni déplaçable, ni au-dessus des traits
+}
+
+// Décode une image PNG ou JPEG
+fn decode_image(bytes: &[u8]) -> Result<ColorImage, image::ImageError> {
+    let image = image::load_from_memory(bytes)?.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+// Mesures affichées par le profileur
+#[derive(Default)]
+struct FrameStats {
+    bake_time: Duration,     // Durée de la dernière mise à jour de la texture
+    baked_triangles: usize,  // Triangles rastérisés lors de cette mise à jour
+    shapes: usize,           // Formes envoyées par la zone de dessin à la dernière image
+    hit_test_time: Duration, // Durée du dernier test de contact (pipette, menu contextuel)
 }
 
 struct PaintApp {
-    lines: Vec<Line>,
-    redo_stack: Vec<Line>, // <-- Pile pour le Redo
-    current_line: Vec<Pos2>,
+    document: Document, // Traits de l'image affichée et leur historique
+    document_events: Receiver<DocumentEvent>, // Modifications de `document`
+    tools: Vec<Box<dyn Tool>>, // Outils dans l'ordre de `BrushMode::ALL`, puis ceux des extensions
+    drawing: bool,      // Geste en cours avec l'outil actif
+    laser_strokes: Vec<(Vec<Pos2>, Instant)>, // Traits du pointeur laser, avec leur fin
+    overlay_path: PathShape, // Tampon des points à l'écran des traits superposés au canevas
     brush_color: Color32,
+    brush_swatch: Option<String>, // Couleur nommée choisie, suivie tant que le pinceau l'a
     brush_size: f32,
+    brush_opacity: f32, // Opacité des nouveaux traits, entre 0 et 1
     mode: BrushMode,
+    frames: Vec<Document>, // Images de l'animation ; celle affichée est dans `document`
+    current_frame: usize,
+    playing: bool,        // Lecture de l'animation en cours
+    fps: f32,             // Vitesse de lecture, en images par seconde
+    next_frame_time: f64, // Instant (temps d'egui) du passage à l'image suivante
+    prefs: Preferences,
+    reference: Option<Reference>,
+    brush_hsva: Hsva, // Couleur en TSV, conservée pour ne pas perdre la teinte d'un gris
+    hex_input: String, // Texte du champ hexadécimal
+    script_source: String, // Script en cours d'édition dans la console
+    script_output: Option<Result<Vec<String>, String>>, // Affichages ou erreur du dernier script
+    toasts: toast::Toasts, // Notifications en cours
+    inspected: Option<u64>, // Identifiant de l'objet détaillé par l'inspecteur
+    context_target: Option<(Pos2, Option<u64>)>, // Point du clic droit, objet qui s'y trouve
+    clipboard: Option<DrawObject>, // Objet copié depuis le menu contextuel
+    name_input: String, // Nom saisi dans l'inspecteur
+    recording_shortcut: Option<&'static str>, // Commande dont le nouveau raccourci est attendu
+    recent_colors: Vec<Color32>, // Dernières couleurs utilisées, la plus récente en premier
+    new_preset_name: String, // Nom saisi pour le prochain préréglage
+    new_swatch_name: String, // Nom saisi pour la prochaine couleur nommée
+    ui_scale_input: f32, // Valeur du curseur d'échelle, appliquée une fois relâché
+    keyboard_mode: bool, // Dessin au clavier (accessibilité)
+    keyboard_cursor: Option<Pos2>, // Position du curseur clavier, centrée au premier usage
+    keyboard_pen_down: bool, // Crayon posé : les déplacements prolongent le trait
+    zoom: f32,        // Agrandissement de la vue
+    pan: Vec2,        // Décalage de la vue : écran = dessin × zoom + pan
+    panning: bool,    // Barre d'espace maintenue : outil main temporaire
+    panel_expanded: bool, // Panneau de réglages déplié
+    focus_mode: bool, // Plein écran sans panneaux (F11)
+    presentation: Option<usize>, // Mode présentation (F5) : nombre de traits révélés
+    welcome_open: bool, // Écran d'accueil affiché
+    new_properties: Properties, // Fond et format choisis sur l'écran d'accueil
+    dirty: bool,      // Dessin modifié depuis l'ouverture ou le dernier enregistrement
+    confirm_clear_open: bool, // Confirmation de « Effacer tout » affichée
+    confirm_close_open: bool, // Confirmation de fermeture affichée
+    properties_edit: Option<Properties>, // Propriétés en cours d'édition, dialogue affiché
+    file_size: Option<Result<usize, String>>, // Taille estimée du fichier, oubliée à chaque changement
+    window_title: String,                     // Dernier titre envoyé à la fenêtre
+    file_path: Option<std::path::PathBuf>,    // Fichier du document ouvert ou enregistré
+    close_allowed: bool, // Fermeture confirmée : la prochaine demande n'est plus annulée
+    show_profiler: bool,
+    show_log: bool,            // Console du journal affichée
+    log_level: tracing::Level, // Niveau le plus détaillé affiché par la console
+    log_filter: String,        // Texte recherché dans les entrées du journal
+    stress_count: usize,       // Nombre de traits créés par le générateur de test
+    stats: FrameStats,
+    mesh_pixels_per_point: f32, // Pixels par unité du dessin des maillages en cache (zoom compris)
+    canvas: Option<TextureHandle>, // Texture contenant tous les traits validés
+    canvas_image: Arc<ColorImage>, // Copie CPU de cette texture, partagée avec egui à l'envoi
+    canvas_rect: Rect,          // Zone de l'écran couverte par la texture
+    baked_lines: usize,         // Nombre de traits déjà rastérisés dans la texture
+    baked_pan: Vec2,            // Décalage de la vue au moment de la rastérisation
 }
 
 impl Default for PaintApp {
     fn default() -> Self {
+        let mut document = Document::with_author(user_name());
         Self {
-            lines: Vec::new(),
-            redo_stack: Vec::new(),
-            current_line: Vec::new(),
+            document_events: document.subscribe(),
+            document,
+            tools: vec![
+                Box::new(Freehand::default()),
+                Box::new(StraightLine::default()),
+                Box::new(Outline::rectangle()),
+                Box::new(Outline::ellipse()),
+                Box::new(Freehand::eraser()),
+                Box::new(Freehand::default()),
+            ],
+            drawing: false,
+            laser_strokes: Vec::new(),
+            overlay_path: PathShape::line(Vec::new(), Stroke::NONE),
             brush_color: Color32::LIGHT_BLUE,
+            brush_swatch: None,
             brush_size: 4.0,
+            brush_opacity: 1.0,
             mode: BrushMode::Freehand,
+            frames: vec![Document::new()],
+            current_frame: 0,
+            playing: false,
+            fps: 8.0,
+            next_frame_time: 0.0,
+            prefs: Preferences::default(),
+            reference: None,
+            brush_hsva: Hsva::from(Color32::LIGHT_BLUE),
+            hex_input: String::new(),
+            script_source: String::new(),
+            script_output: None,
+            toasts: toast::Toasts::default(),
+            inspected: None,
+            context_target: None,
+            clipboard: None,
+            name_input: String::new(),
+            recording_shortcut: None,
+            recent_colors: Vec::new(),
+            new_preset_name: String::new(),
+            new_swatch_name: String::new(),
+            ui_scale_input: 1.0,
+            keyboard_mode: false,
+            keyboard_cursor: None,
+            keyboard_pen_down: false,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+            panning: false,
+            panel_expanded: true,
+            focus_mode: false,
+            presentation: None,
+            welcome_open: false,
+            new_properties: Properties::default(),
+            dirty: false,
+            confirm_clear_open: false,
+            confirm_close_open: false,
+            properties_edit: None,
+            file_size: None,
+            close_allowed: false,
+            window_title: String::new(),
+            file_path: None,
+            show_profiler: false,
+            show_log: false,
+            log_level: tracing::Level::INFO,
+            log_filter: String::new(),
+            stress_count: 1000,
+            stats: FrameStats::default(),
+            mesh_pixels_per_point: 0.0,
+            canvas: None,
+            canvas_image: Arc::default(),
+            canvas_rect: Rect::NOTHING,
+            baked_lines: 0,
+            baked_pan: Vec2::ZERO,
         }
     }
 }
 
 impl PaintApp {
+    // Création de l'application, avec les préférences de la session précédente
+    // et les réglages de la ligne de commande
+    fn new(cc: &eframe::CreationContext<'_>, args: cli::Args) -> Self {
+        let mut app = Self::default();
+        if let Some(prefs) = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, PREFERENCES_KEY))
+        {
+            app.prefs = prefs;
+        }
+        if let Some(language) = args.lang {
+            app.prefs.language = language;
+        }
+        app.ui_scale_input = app.prefs.ui_scale;
+        app.welcome_open = app.prefs.show_welcome;
+        app.new_properties.background = Some(app.prefs.canvas_background);
+        cc.egui_ctx.set_zoom_factor(app.prefs.ui_scale);
+        cc.egui_ctx.set_visuals(app.prefs.theme.visuals());
+        i18n::set_language(app.prefs.language);
+        #[cfg(feature = "plugins")]
+        for error in rust_paint::plugin::load_plugins(PLUGINS_DIR.as_ref(), &mut app.tools) {
+            app.toasts
+                .error(tr_fill("Extension ignorée : {}", &[&error]));
+        }
+        if let Some(path) = args.file {
+            app.open_file(path);
+        }
+        app
+    }
+
+    // Reprend l'outil, la couleur, la taille et l'opacité d'un préréglage
+    fn apply_preset(&mut self, index: usize) {
+        if let Some(preset) = self.prefs.presets.get(index) {
+            self.mode = preset.mode.clone();
+            self.brush_color = preset.color;
+            self.brush_size = preset.size;
+            self.brush_opacity = preset.opacity;
+        }
+    }
+
+    // Place une couleur en tête des couleurs récentes, sans doublon
+    fn remember_color(&mut self, color: Color32) {
+        self.recent_colors.retain(|&recent| recent != color);
+        self.recent_colors.insert(0, color);
+        self.recent_colors.truncate(RECENT_COLORS);
+    }
+
+    // Place un document en tête des documents récents, sans doublon
+    fn remember_file(&mut self, path: &std::path::Path) {
+        // Chemin absolu : l'application peut être relancée depuis un autre dossier
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+        self.prefs.recent_files.retain(|recent| *recent != path);
+        self.prefs.recent_files.insert(0, path);
+        self.prefs.recent_files.truncate(RECENT_FILES);
+    }
+
+    // Marge du test de contact autour des traits, selon le zoom et le périphérique utilisé
+    fn hit_tolerance(&self, ctx: &egui::Context) -> f32 {
+        let device = if ctx.input(|i| i.any_touches()) {
+            InputDevice::Touch
+        } else {
+            InputDevice::Mouse
+        };
+        geometry::hit_tolerance(self.zoom, device)
+    }
+
+    // Indice de l'objet le plus haut sous `pos` (coordonnées du dessin) ; la durée du test
+    // est mesurée pour le profileur
+    fn object_at(&mut self, ctx: &egui::Context, pos: Pos2) -> Option<usize> {
+        let tolerance = self.hit_tolerance(ctx);
+        let start = Instant::now();
+        let hit = self.document.top_object_at(pos, tolerance);
+        self.stats.hit_test_time = start.elapsed();
+        hit
+    }
+
+    // Pipette : reprend la couleur de l'objet visible sous `pos` (coordonnées du dessin)
+    fn pick_color(&mut self, ctx: &egui::Context, pos: Pos2) {
+        let top = self
+            .object_at(ctx, pos)
+            .map(|index| &self.document.objects()[index]);
+        if let Some(object) = top
+            && !object.is_eraser()
+            && let Some(color) = object.color()
+        {
+            self.brush_color = color;
+        }
+    }
+
     // Logique pour annuler
     fn undo(&mut self) {
-        if let Some(line) = self.lines.pop() {
-            self.redo_stack.push(line);
-        }
+        self.document.undo();
     }
 
     // Logique pour rétablir
     fn redo(&mut self) {
-        if let Some(line) = self.redo_stack.pop() {
-            self.lines.push(line);
-        }
+        self.document.redo();
     }
-}
 
-impl eframe::App for PaintApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        
-        // --- Gestion des raccourcis clavier ---
-        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Z)) {
-            self.undo();
+    // Ajoute `count` traits aléatoires dans la zone de dessin (tests de performance)
+    fn generate_stress_strokes(&mut self, count: usize) {
+        let area = Rect::from_min_max(
+            self.to_drawing(self.canvas_rect.min),
+            self.to_drawing(self.canvas_rect.max),
+        );
+        if !area.is_positive() {
+            return;
         }
-        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Y)) {
-            self.redo();
+        // Un seul lot : une annulation retire tous les traits générés
+        let mut rng = fastrand::Rng::new();
+        let mut strokes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut point = Pos2::new(
+                area.min.x + rng.f32() * area.width(),
+                area.min.y + rng.f32() * area.height(),
+            );
+            let mut points = Vec::with_capacity(32);
+            for _ in 0..rng.usize(2..32) {
+                points.push(point);
+                point = area.clamp(point + egui::vec2(rng.f32() - 0.5, rng.f32() - 0.5) * 40.0);
+            }
+            let color = Color32::from_rgb(rng.u8(..), rng.u8(..), rng.u8(..));
+            let line = Line::new(points, color, 1.0 + rng.f32() * 15.0, 1.0);
+            strokes.push(Action::Push(line.into()));
         }
+        self.document.apply(Action::Batch(strokes));
+    }
 
-        // --- UI : Panneau de réglages ---
-        egui::SidePanel::left("settings").show(ctx, |ui| {
-            ui.heading("Outils");
-            
-            ui.horizontal(|ui| {
-                ui.selectable_value(&mut self.mode, BrushMode::Freehand, "✏ Main levée");
-                ui.selectable_value(&mut self.mode, BrushMode::StraightLine, "📏 Ligne");
-                ui.selectable_value(&mut self.mode, BrushMode::Eraser, "🧽 Gomme");
-            });
+    // Affiche l'image `index` de l'animation. Son document passe dans `document`, celui de
+    // l'image quittée retourne à sa place ; chaque image garde son propre historique.
+    fn go_to_frame(&mut self, index: usize) {
+        if index == self.current_frame || index >= self.frames.len() {
+            return;
+        }
+        // Les modifications en attente sont traitées avant de changer d'abonnement
+        self.document_changes();
+        std::mem::swap(&mut self.document, &mut self.frames[self.current_frame]);
+        std::mem::swap(&mut self.document, &mut self.frames[index]);
+        self.document_events = self.document.subscribe();
+        self.current_frame = index;
+        self.cancel_gesture();
+        self.keyboard_pen_down = false;
+        self.invalidate_canvas();
+    }
+
+    // Ajoute une image après l'image courante, vide ou copie de celle-ci, et l'affiche
+    fn insert_frame(&mut self, duplicate: bool) {
+        let document = if duplicate {
+            self.document.fork()
+        } else {
+            // La palette et les propriétés sont celles de toute l'animation
+            self.document.fork_empty()
+        };
+        self.frames.insert(self.current_frame + 1, document);
+        self.go_to_frame(self.current_frame + 1);
+        self.dirty = true;
+        self.file_size = None;
+    }
 
-            ui.separator();
+    // Supprime l'image courante (il en reste toujours au moins une)
+    fn delete_frame(&mut self) {
+        if self.frames.len() < 2 {
+            return;
+        }
+        self.document_changes();
+        self.frames.remove(self.current_frame);
+        self.current_frame = self.current_frame.min(self.frames.len() - 1);
+        self.document = std::mem::take(&mut self.frames[self.current_frame]);
+        self.document_events = self.document.subscribe();
+        self.cancel_gesture();
+        self.keyboard_pen_down = false;
+        self.invalidate_canvas();
+        self.dirty = true;
+        self.file_size = None;
+    }
 
-            ui.add(egui::Slider::new(&mut self.brush_size, 1.0..=50.0).text("Taille"));
-            
-            if self.mode != BrushMode::Eraser {
-                ui.color_edit_button_srgba(&mut self.brush_color);
-            } else {
-                ui.label("Mode Gomme actif");
+    // Remplace l'animation par un document vide, sans historique ni fichier
+    fn new_document(&mut self, properties: Properties) {
+        let mut document = Document::with_author(user_name());
+        let properties = Properties {
+            author: document.properties().author.clone(),
+            ..properties
+        };
+        document.set_properties(properties.clone());
+        self.document_events = document.subscribe();
+        self.document = document;
+        let mut frame = Document::new();
+        frame.set_properties(properties);
+        self.frames = vec![frame];
+        self.current_frame = 0;
+        self.cancel_gesture();
+        self.invalidate_canvas();
+        self.file_path = None;
+        self.dirty = false;
+        self.file_size = None;
+    }
+
+    // Remplace l'animation par le document enregistré dans `path` ; les objets ajoutés
+    // ensuite sont attribués à l'utilisateur actuel
+    fn open_file(&mut self, path: std::path::PathBuf) {
+        let read = std::fs::read_to_string(&path).map_err(|error| error.to_string());
+        match read.and_then(|text| export::decode_frames(&text)) {
+            Ok(mut frames) => {
+                for frame in &mut frames {
+                    frame.set_author(user_name());
+                }
+                let mut document = std::mem::take(&mut frames[0]);
+                tracing::info!(
+                    path = %path.display(),
+                    objects = document.len(),
+                    frames = frames.len(),
+                    "document ouvert"
+                );
+                self.document_events = document.subscribe();
+                self.document = document;
+                self.frames = frames;
+                self.current_frame = 0;
+                self.cancel_gesture();
+                self.invalidate_canvas();
+                self.remember_file(&path);
+                self.file_path = Some(path);
+                self.dirty = false;
+                self.file_size = None;
+                self.welcome_open = false;
+            }
+            Err(error) => {
+                let name = path.display().to_string();
+                let text = tr_fill("Document « {} » illisible : {}", &[&name, &error]);
+                let retry = Box::new(move |app: &mut Self, _: &egui::Context| app.open_file(path));
+                self.toasts.error_with_retry(text, retry);
             }
-            
-            ui.separator();
+        }
+    }
 
-            // Boutons Undo / Redo
-            ui.horizontal(|ui| {
-                if ui.button("↩ Annuler").on_hover_text("Ctrl+Z").clicked() {
-                    self.undo();
+    // Images de l'animation dans l'ordre, l'image affichée comprise
+    fn all_frames(&self) -> Vec<&Document> {
+        let current = self.current_frame;
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                if index == current {
+                    &self.document
+                } else {
+                    frame
                 }
-                if ui.button("↪ Rétablir").on_hover_text("Ctrl+Y").clicked() {
-                    self.redo();
+            })
+            .collect()
+    }
+
+    // Nom des fichiers écrits : celui du document ouvert, à défaut son titre
+    fn file_stem(&self) -> String {
+        let title = &self.document.properties().title;
+        self.file_path
+            .as_deref()
+            .and_then(std::path::Path::file_stem)
+            .map_or_else(
+                || {
+                    if title.is_empty() {
+                        tr("sans-titre").to_owned()
+                    } else {
+                        title.clone()
+                    }
+                },
+                |stem| stem.to_string_lossy().into(),
+            )
+    }
+
+    // Enregistre toutes les images dans le fichier du document, ou dans un nouveau fichier
+    // .rpaint s'il n'en a pas encore. Retourne `true` si le document a été écrit
+    fn save_file(&mut self) -> bool {
+        let saved =
+            export::encode_frames(&self.all_frames()).and_then(|bytes| match &self.file_path {
+                // Dans le navigateur, chaque enregistrement est un nouveau téléchargement
+                #[cfg(not(target_arch = "wasm32"))]
+                Some(path) => {
+                    std::fs::write(path, bytes).map_err(|error| error.to_string())?;
+                    Ok(path.display().to_string())
                 }
+                _ => export::save(&self.file_stem(), export::Format::Rpaint, &bytes),
             });
+        match saved {
+            Ok(path) => {
+                tracing::info!(%path, frames = self.frames.len(), "document enregistré");
+                self.toasts
+                    .info(tr_fill("Document enregistré : {}", &[&path]));
+                // Dans le navigateur, `path` n'est que le nom du fichier téléchargé
+                #[cfg(not(target_arch = "wasm32"))]
+                self.remember_file(path.as_ref());
+                self.file_path = Some(path.into());
+                self.dirty = false;
+                true
+            }
+            Err(error) => {
+                self.toasts
+                    .error(tr_fill("Enregistrement impossible : {}", &[&error]));
+                false
+            }
+        }
+    }
 
-            if ui.button("🗑 Effacer tout").clicked() {
-                self.lines.clear();
-                self.redo_stack.clear();
+    // Exporte l'image affichée
+    fn export(&mut self, format: export::Format) {
+        let name = self.file_stem();
+        let saved = export::encode(&self.document, format)
+            .and_then(|bytes| export::save(&name, format, &bytes));
+        match saved {
+            Ok(path) => self.toasts.info(tr_fill("Dessin exporté : {}", &[&path])),
+            Err(error) => self
+                .toasts
+                .error(tr_fill("Export impossible : {}", &[&error])),
+        }
+    }
+
+    // Exporte chaque image de l'animation en PNG numéroté : « nom-001.png », « nom-002.png »…
+    fn export_frames(&mut self) {
+        let name = self.file_stem();
+        let saved = export::encode_png_frames(&self.all_frames()).and_then(|images| {
+            images
+                .iter()
+                .enumerate()
+                .map(|(index, bytes)| {
+                    let name = format!("{name}-{:03}", index + 1);
+                    export::save(&name, export::Format::Png, bytes)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        match saved {
+            Ok(paths) => self.toasts.info(tr_fill(
+                "Animation exportée : {} images ({}…)",
+                &[&paths.len().to_string(), &paths[0]],
+            )),
+            Err(error) => self
+                .toasts
+                .error(tr_fill("Export impossible : {}", &[&error])),
+        }
+    }
+
+    // Applique les propriétés éditées à toutes les images de l'animation
+    fn apply_properties(&mut self, properties: Properties) {
+        for frame in &mut self.frames {
+            frame.set_properties(properties.clone());
+        }
+        self.document.set_properties(properties);
+        self.dirty = true;
+        self.file_size = None;
+    }
+
+    // Suit les modifications du document affiché, quelle qu'en soit l'origine (outil,
+    // script, annulation…). La palette est celle de toute l'animation : une couleur nommée
+    // changée dans l'image affichée l'est aussi dans les autres, où le changement entre dans
+    // leur propre historique
+    fn document_changes(&mut self) {
+        while let Ok(event) = self.document_events.try_recv() {
+            self.dirty = true;
+            self.file_size = None;
+            match event {
+                DocumentEvent::Removed(ids)
+                    if self.inspected.is_some_and(|id| ids.contains(&id)) =>
+                {
+                    self.inspected = None;
+                }
+                DocumentEvent::PaletteChanged(name) => {
+                    let color = self.document.swatch(&name);
+                    let current = self.current_frame;
+                    let others = self
+                        .frames
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(index, _)| *index != current)
+                        .map(|(_, frame)| frame);
+                    Document::set_swatch_everywhere(others, &name, color);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Fond du canevas : celui du document s'il en a un, sinon celui des préférences
+    fn canvas_background(&self) -> Color32 {
+        self.document
+            .properties()
+            .background
+            .unwrap_or(self.prefs.canvas_background)
+    }
+
+    // Image suivante ou précédente, en bouclant
+    fn step_frame(&mut self, forward: bool) {
+        let count = self.frames.len();
+        let index = if forward {
+            (self.current_frame + 1) % count
+        } else {
+            (self.current_frame + count - 1) % count
+        };
+        self.go_to_frame(index);
+    }
+
+    // Charge l'image déposée sur la fenêtre comme référence, à sa taille réelle à l'écran,
+    // réduite si besoin pour tenir dans la vue
+    fn load_reference(&mut self, ctx: &egui::Context, file: &egui::DroppedFile) {
+        let bytes = match (&file.bytes, &file.path) {
+            (Some(bytes), _) => Ok(bytes.to_vec()),
+            (None, Some(path)) => std::fs::read(path).map_err(|error| error.to_string()),
+            (None, None) => return,
+        };
+        let image = match bytes.and_then(|bytes| decode_image(&bytes).map_err(|e| e.to_string())) {
+            Ok(image) => image,
+            Err(error) => {
+                let text = tr_fill("Image illisible : {}", &[&error]);
+                // Relire le fichier n'a de sens que s'il est sur le disque
+                if file.path.is_some() {
+                    let file = file.clone();
+                    let retry = Box::new(move |app: &mut Self, ctx: &egui::Context| {
+                        app.load_reference(ctx, &file);
+                    });
+                    self.toasts.error_with_retry(text, retry);
+                } else {
+                    self.toasts.error(text);
+                }
+                return;
             }
+        };
+        let view = Rect::from_min_max(
+            self.to_drawing(self.canvas_rect.min),
+            self.to_drawing(self.canvas_rect.max),
+        );
+        let size = egui::vec2(image.size[0] as f32, image.size[1] as f32)
+            / (ctx.pixels_per_point() * self.zoom);
+        let fit = (view.width() / size.x).min(view.height() / size.y).min(1.0);
+        self.reference = Some(Reference {
+            texture: ctx.load_texture("reference", image, TextureOptions::LINEAR),
+            rect: Rect::from_min_size(view.min, size * fit),
+            opacity: 0.5,
+            above: false,
+            visible: true,
+            locked: false,
         });
+    }
 
-        // --- Zone de dessin ---
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::drag());
-            
-            let current_color = if self.mode == BrushMode::Eraser {
-                ui.visuals().panel_fill
-            } else {
-                self.brush_color
-            };
+    // Dessine l'image de référence si elle est visible et du bon côté des traits
+    fn draw_reference(&mut self, painter: &egui::Painter, above: bool) {
+        if let Some(reference) = &self.reference
+            && reference.visible
+            && reference.above == above
+        {
+            self.stats.shapes += 1;
+            let rect = Rect::from_min_max(
+                self.to_screen(reference.rect.min),
+                self.to_screen(reference.rect.max),
+            );
+            painter.image(
+                reference.texture.id(),
+                rect,
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE.gamma_multiply(reference.opacity),
+            );
+        }
+    }
 
-            // 1. Gestion des entrées
-            if let Some(pointer_pos) = response.interact_pointer_pos() {
-                match self.mode {
-                    BrushMode::Freehand | BrushMode::Eraser => {
-                        if response.dragged() {
-                            self.current_line.push(pointer_pos);
+    // Mémoire utilisée par les objets (presse-papiers compris) et par l'historique
+    // (annulation et rétablissement) de toutes les images de l'animation
+    fn memory_usage(&self) -> (usize, usize) {
+        let documents = || std::iter::once(&self.document).chain(&self.frames);
+        let clipboard = self.clipboard.as_ref().map_or(0, DrawObject::memory_size);
+        let drawing = clipboard + documents().map(Document::memory_size).sum::<usize>();
+        let history = documents()
+            .map(|document| document.undo_memory_size() + document.redo_memory_size())
+            .sum();
+        (drawing, history)
+    }
+
+    // Oublie l'historique de toutes les images : la mémoire des objets supprimés est rendue
+    fn clear_history(&mut self) {
+        self.document.clear_history();
+        for frame in &mut self.frames {
+            frame.clear_history();
+        }
+    }
+
+    // Contenu du panneau de réglages, organisé en sections repliables
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        egui::CollapsingHeader::new(tr("✏ Outils"))
+            .id_source("tools")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (mode, icon, label) in [
+                        (BrushMode::Freehand, "✏", tr("Main levée")),
+                        (BrushMode::StraightLine, "📏", tr("Ligne")),
+                        (BrushMode::Rectangle, "⬜", tr("Rectangle")),
+                        (BrushMode::Ellipse, "⭕", tr("Ellipse")),
+                        (BrushMode::Eraser, "🧽", tr("Gomme")),
+                        (BrushMode::Laser, "🔦", tr("Pointeur laser")),
+                    ]
+                    .into_iter()
+                    .chain(self.plugin_tools())
+                    {
+                        // État sélectionné exposé : le changement d'outil est annoncé
+                        let selected = self.mode == mode;
+                        let response = ui.selectable_label(selected, icon).on_hover_text(
+                            mode.command().map_or(label.to_owned(), |command| {
+                                command.hint(&ctx, &self.prefs.shortcuts)
+                            }),
+                        );
+                        response.widget_info(|| {
+                            egui::WidgetInfo::selected(
+                                egui::WidgetType::SelectableLabel,
+                                selected,
+                                label,
+                            )
+                        });
+                        if response.clicked() {
+                            self.select_tool(&ctx, mode);
+                        }
+                    }
+                });
+                ui.add(egui::Slider::new(&mut self.brush_size, 1.0..=50.0).text(tr("Taille")));
+                if self.mode != BrushMode::Eraser {
+                    ui.add(
+                        egui::Slider::new(&mut self.brush_opacity, 0.05..=1.0)
+                            .fixed_decimals(2)
+                            .text(tr("Opacité")),
+                    );
+                } else {
+                    ui.label(tr("Mode Gomme actif"));
+                }
+                if ui
+                    .checkbox(&mut self.keyboard_mode, tr("⌨ Dessin au clavier"))
+                    .on_hover_text(tr("Flèches : déplacer (Maj : plus vite)\n\
+                         Entrée : poser / lever le crayon\n\
+                         Échap : abandonner le trait"))
+                    .changed()
+                    && !self.keyboard_mode
+                {
+                    self.keyboard_pen_down = false;
+                    self.cancel_gesture();
+                }
+            });
+
+        if self.mode != BrushMode::Eraser {
+            egui::CollapsingHeader::new(tr("🎨 Couleur"))
+                .id_source("color")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let button = ui.color_edit_button_srgba(&mut self.brush_color);
+                        accessible_label(
+                            &button,
+                            egui::WidgetType::ColorButton,
+                            tr("Couleur du pinceau"),
+                        );
+
+                        // Saisie en hexadécimal, resynchronisée hors édition
+                        let hex = ui.add(
+                            egui::TextEdit::singleline(&mut self.hex_input)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(80.0),
+                        );
+                        accessible_label(
+                            &hex,
+                            egui::WidgetType::TextEdit,
+                            tr("Couleur hexadécimale"),
+                        );
+                        if hex.changed()
+                            && let Some(color) = parse_hex_color(&self.hex_input)
+                        {
+                            self.brush_color = color;
+                        }
+                        if !hex.has_focus() {
+                            self.hex_input = color_to_hex(self.brush_color);
                         }
+                    });
+
+                    // Curseurs TSV : la teinte est conservée pour les gris
+                    if Color32::from(self.brush_hsva) != self.brush_color {
+                        self.brush_hsva = Hsva::from(self.brush_color);
                     }
-                    BrushMode::StraightLine => {
-                        if response.dragged() {
-                            if self.current_line.is_empty() {
-                                self.current_line.push(pointer_pos);
+                    egui::CollapsingHeader::new(tr("TSV"))
+                        .id_source("hsv")
+                        .show(ui, |ui| {
+                            let mut hsva = self.brush_hsva;
+                            let mut hue = hsva.h * 360.0;
+                            ui.add(
+                                egui::Slider::new(&mut hue, 0.0..=360.0)
+                                    .suffix("°")
+                                    .text(tr("Teinte")),
+                            );
+                            hsva.h = hue / 360.0;
+                            ui.add(
+                                egui::Slider::new(&mut hsva.s, 0.0..=1.0).text(tr("Saturation")),
+                            );
+                            ui.add(egui::Slider::new(&mut hsva.v, 0.0..=1.0).text(tr("Valeur")));
+                            if hsva != self.brush_hsva {
+                                self.brush_hsva = hsva;
+                                self.brush_color = Color32::from(hsva);
+                            }
+                        });
+
+                    // Palette : défaut puis ajouts (clic droit pour retirer)
+                    let mut removed = None;
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+                        for color in DEFAULT_SWATCHES {
+                            if color_swatch(ui, color, color == self.brush_color).clicked() {
+                                self.brush_color = color;
+                            }
+                        }
+                        for (index, &color) in self.prefs.swatches.iter().enumerate() {
+                            let swatch = color_swatch(ui, color, color == self.brush_color)
+                                .on_hover_text(tr("Clic droit pour retirer"));
+                            if swatch.clicked() {
+                                self.brush_color = color;
                             }
-                            if self.current_line.len() > 1 {
-                                self.current_line.pop();
+                            if swatch.secondary_clicked() {
+                                removed = Some(index);
                             }
-                            self.current_line.push(pointer_pos);
                         }
+                        let known = DEFAULT_SWATCHES.contains(&self.brush_color)
+                            || self.prefs.swatches.contains(&self.brush_color);
+                        let add = ui
+                            .add_enabled(!known, egui::Button::new("+").small())
+                            .on_hover_text(tr("Ajouter la couleur actuelle à la palette"));
+                        accessible_label(
+                            &add,
+                            egui::WidgetType::Button,
+                            tr("Ajouter à la palette"),
+                        );
+                        if add.clicked() {
+                            self.prefs.swatches.push(self.brush_color);
+                        }
+                    });
+                    if let Some(index) = removed {
+                        self.prefs.swatches.remove(index);
+                    }
+
+                    self.named_colors_ui(ui);
+
+                    if !self.recent_colors.is_empty() {
+                        ui.label(tr("Récentes"));
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+                            for &color in &self.recent_colors {
+                                if color_swatch(ui, color, color == self.brush_color).clicked() {
+                                    self.brush_color = color;
+                                }
+                            }
+                        });
                     }
+                });
+        }
+
+        // Préréglages : un clic (ou la touche 1 à 9) reprend les réglages enregistrés
+        egui::CollapsingHeader::new(tr("⭐ Préréglages"))
+            .id_source("presets")
+            .default_open(true)
+            .show(ui, |ui| {
+                let mut applied = None;
+                let mut removed = None;
+                for (index, preset) in self.prefs.presets.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let mut button = ui.button(&preset.name);
+                        if let Some(key) = PRESET_KEYS.get(index) {
+                            button = button.on_hover_text(tr_fill("Touche {}", &[key.name()]));
+                        }
+                        if button.clicked() {
+                            applied = Some(index);
+                        }
+                        let delete = ui.small_button("🗑").on_hover_text(tr("Supprimer"));
+                        let label = tr_fill("Supprimer le préréglage {}", &[&preset.name]);
+                        accessible_label(&delete, egui::WidgetType::Button, &label);
+                        if delete.clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = applied {
+                    self.apply_preset(index);
+                }
+                if let Some(index) = removed {
+                    self.prefs.presets.remove(index);
                 }
-            } else if !self.current_line.is_empty() {
-                // Quand on termine un trait :
-                // On vide la redo_stack car une nouvelle action invalide le futur précédent
-                self.redo_stack.clear();
-                
-                self.lines.push(Line {
-                    points: std::mem::take(&mut self.current_line),
-                    color: current_color,
-                    width: self.brush_size,
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_preset_name)
+                            .hint_text(tr("Nom"))
+                            .desired_width(100.0),
+                    );
+                    let name = self.new_preset_name.trim();
+                    if ui
+                        .add_enabled(!name.is_empty(), egui::Button::new(tr("💾 Enregistrer")))
+                        .clicked()
+                    {
+                        self.prefs.presets.push(ToolPreset {
+                            name: name.to_owned(),
+                            mode: self.mode.clone(),
+                            color: self.brush_color,
+                            size: self.brush_size,
+                            opacity: self.brush_opacity,
+                        });
+                        self.new_preset_name.clear();
+                    }
                 });
-            }
+            });
+
+        egui::CollapsingHeader::new(tr("↩ Historique"))
+            .id_source("history")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    self.command_button(ui, "edit.undo");
+                    self.command_button(ui, "edit.redo");
+                    self.command_button(ui, "edit.clear");
+                });
+            });
+
+        egui::CollapsingHeader::new(tr("📤 Exporter"))
+            .id_source("export")
+            .show(ui, |ui| {
+                self.command_button(ui, "file.save");
+                ui.horizontal(|ui| {
+                    self.command_button(ui, "file.export_png");
+                    self.command_button(ui, "file.export_svg");
+                });
+                self.command_button(ui, "file.export_frames");
+                self.command_button(ui, "file.properties");
+            });
 
-            // 2. Rendu : Historique
-            for line in &self.lines {
-                if line.points.len() >= 2 {
-                    painter.add(egui::Shape::line(
-                        line.points.clone(),
-                        Stroke::new(line.width, line.color),
+        egui::CollapsingHeader::new(tr("🎞 Animation"))
+            .id_source("animation")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    self.command_button(ui, "frame.previous");
+                    ui.label(tr_fill(
+                        "Image {} / {}",
+                        &[
+                            &(self.current_frame + 1).to_string(),
+                            &self.frames.len().to_string(),
+                        ],
                     ));
+                    self.command_button(ui, "frame.next");
+                });
+                ui.horizontal(|ui| {
+                    self.command_button(ui, "frame.new");
+                    self.command_button(ui, "frame.duplicate");
+                    self.command_button(ui, "frame.delete");
+                });
+                ui.horizontal(|ui| {
+                    let (icon, label) = if self.playing {
+                        ("⏸", tr("Pause"))
+                    } else {
+                        ("⏵", tr("Lecture"))
+                    };
+                    if icon_button(ui, icon, label).clicked() {
+                        self.run_command(&ctx, "frame.play");
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.fps, 1.0..=30.0)
+                            .step_by(1.0)
+                            .text(tr("images/s")),
+                    );
+                });
+            });
+
+        egui::CollapsingHeader::new(tr("🖌 Apparence"))
+            .id_source("appearance")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let theme = self.prefs.theme;
+                    ui.selectable_value(&mut self.prefs.theme, Theme::Light, tr("☀ Clair"));
+                    ui.selectable_value(&mut self.prefs.theme, Theme::Dark, tr("🌙 Sombre"));
+                    ui.selectable_value(
+                        &mut self.prefs.theme,
+                        Theme::HighContrast,
+                        tr("◑ Contraste élevé"),
+                    );
+                    if self.prefs.theme != theme {
+                        ui.ctx().set_visuals(self.prefs.theme.visuals());
+                    }
+                });
+                ui.checkbox(
+                    &mut self.prefs.panel_right,
+                    tr("Panneau à droite (gaucher)"),
+                );
+                egui::ComboBox::from_label(tr("Langue"))
+                    .selected_text(self.prefs.language.name())
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(
+                                &mut self.prefs.language,
+                                language,
+                                language.name(),
+                            );
+                        }
+                    });
+                i18n::set_language(self.prefs.language);
+                // Appliquée au relâchement, sinon le curseur bougerait sous la souris
+                let scale = ui
+                    .add(
+                        egui::Slider::new(&mut self.ui_scale_input, 0.5..=3.0)
+                            .step_by(0.05)
+                            .suffix("×")
+                            .text(tr("Échelle de l'interface")),
+                    )
+                    .on_hover_text("Ctrl + / Ctrl - / Ctrl 0");
+                if scale.drag_released() || (scale.changed() && !scale.dragged()) {
+                    self.prefs.ui_scale = self.ui_scale_input;
+                    ui.ctx().set_zoom_factor(self.ui_scale_input);
                 }
-            }
+                ui.label(tr("Fond du canevas"));
+                background_picker(ui, &mut self.prefs.canvas_background);
+            });
 
-            // 3. Rendu : Prévisualisation
-            if self.current_line.len() >= 2 {
-                painter.add(egui::Shape::line(
-                    self.current_line.clone(),
-                    Stroke::new(self.brush_size, current_color),
-                ));
+        egui::CollapsingHeader::new(tr("⌨ Raccourcis"))
+            .id_source("shortcuts")
+            .show(ui, |ui| self.shortcuts_ui(ui));
+
+        egui::CollapsingHeader::new(tr("🖼 Référence"))
+            .id_source("reference")
+            .show(ui, |ui| match &mut self.reference {
+                Some(reference) => {
+                    ui.checkbox(&mut reference.visible, tr("Afficher (R)"));
+                    ui.add(
+                        egui::Slider::new(&mut reference.opacity, 0.05..=1.0)
+                            .fixed_decimals(2)
+                            .text(tr("Opacité")),
+                    );
+                    ui.checkbox(&mut reference.locked, tr("🔒 Verrouiller comme fond"))
+                        .on_hover_text(tr("Sinon, Ctrl + glisser pour la déplacer"));
+                    if reference.locked {
+                        reference.above = false;
+                    }
+                    ui.add_enabled(
+                        !reference.locked,
+                        egui::Checkbox::new(&mut reference.above, tr("Au-dessus des traits")),
+                    );
+                    if ui.button(tr("Retirer l'image")).clicked() {
+                        self.reference = None;
+                    }
+                }
+                None => {
+                    ui.label(tr("Déposez une image PNG ou JPEG sur la fenêtre."));
+                }
+            });
+
+        egui::CollapsingHeader::new(tr("📜 Script"))
+            .id_source("script")
+            .show(ui, |ui| self.script_ui(ui));
+
+        egui::CollapsingHeader::new(tr("🔎 Inspecteur"))
+            .id_source("inspector")
+            .show(ui, |ui| self.inspector_ui(ui));
+
+        // Statistiques du dessin, calculées seulement lorsque la section est ouverte
+        egui::CollapsingHeader::new(tr("📊 Statistiques"))
+            .id_source("statistics")
+            .show(ui, |ui| self.statistics_ui(ui));
+
+        egui::CollapsingHeader::new(tr("⚙ Performances"))
+            .id_source("performance")
+            .show(ui, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.prefs.memory_budget_mb, 16.0..=4096.0)
+                        .logarithmic(true)
+                        .suffix(tr(" Mo"))
+                        .text(tr("Budget mémoire")),
+                );
+                let shortcut = self.shortcut_text(ui.ctx(), "view.profiler");
+                ui.checkbox(&mut self.show_profiler, tr("Profileur"))
+                    .on_hover_text(shortcut);
+                let shortcut = self.shortcut_text(ui.ctx(), "view.log");
+                ui.checkbox(&mut self.show_log, tr("Journal"))
+                    .on_hover_text(shortcut);
+
+                // Génération de documents de test, pour le travail sur les performances
+                egui::CollapsingHeader::new(tr("Débogage"))
+                    .id_source("debug")
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let count = ui.add(
+                                egui::DragValue::new(&mut self.stress_count)
+                                    .clamp_range(1..=100_000),
+                            );
+                            accessible_label(
+                                &count,
+                                egui::WidgetType::DragValue,
+                                tr("Nombre de traits"),
+                            );
+                            if ui.button(tr("Générer des traits")).clicked() {
+                                self.generate_stress_strokes(self.stress_count);
+                            }
+                        });
+                    });
+            });
+    }
+
+    // Console de scripts : éditeur, scripts du dossier `SCRIPTS_DIR` et résultat
+    fn script_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(tr("▶ Exécuter")).clicked() {
+                self.run_command(ui.ctx(), "script.run");
             }
+            ui.menu_button("📂", |ui| {
+                let mut scripts: Vec<std::path::PathBuf> = std::fs::read_dir(SCRIPTS_DIR)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension == "rhai")
+                    })
+                    .collect();
+                scripts.sort();
+                if scripts.is_empty() {
+                    ui.label(tr("Aucun script"));
+                }
+                for path in scripts {
+                    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                    if ui.button(name).clicked() {
+                        self.load_script(path);
+                        ui.close_menu();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(tr_fill("Scripts du dossier « {} »", &[SCRIPTS_DIR]));
+            ui.label("❓").on_hover_text(script::API);
         });
+        ui.add(
+            egui::TextEdit::multiline(&mut self.script_source)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY)
+                .hint_text("for x in 0..10 {\n    circle(40 + x * 40, 40, 15);\n}"),
+        );
+        match &self.script_output {
+            Some(Ok(printed)) => {
+                for text in printed {
+                    ui.monospace(text);
+                }
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            None => {}
+        }
+    }
+
+    // Dialogue des propriétés du document : les modifications ne sont appliquées qu'à la
+    // validation, à toutes les images de l'animation
+    fn properties_window(&mut self, ctx: &egui::Context) {
+        let Some(properties) = &mut self.properties_edit else {
+            return;
+        };
+        let date = |millis: u64| {
+            if millis == 0 {
+                tr("inconnue").to_owned()
+            } else {
+                format_age(millis)
+            }
+        };
+        let mut applied = false;
+        let mut closed = false;
+        egui::Window::new(tr("📝 Propriétés du document"))
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("properties").num_columns(2).show(ui, |ui| {
+                    ui.label(tr("Titre"));
+                    ui.text_edit_singleline(&mut properties.title);
+                    ui.end_row();
+                    ui.label(tr("Auteur"));
+                    ui.text_edit_singleline(&mut properties.author);
+                    ui.end_row();
+                    ui.label(tr("Description"));
+                    ui.text_edit_multiline(&mut properties.description);
+                    ui.end_row();
+                    ui.label(tr("Créé"));
+                    ui.label(date(properties.created_at));
+                    ui.end_row();
+                    ui.label(tr("Modifié"));
+                    ui.label(date(properties.modified_at));
+                    ui.end_row();
+                });
+                let mut own_background = properties.background.is_some();
+                ui.checkbox(&mut own_background, tr("Fond propre au document"));
+                match (own_background, &mut properties.background) {
+                    (true, Some(background)) => background_picker(ui, background),
+                    (true, background) => *background = Some(self.prefs.canvas_background),
+                    (false, background) => *background = None,
+                }
+                size_picker(ui, &mut properties.size);
+                ui.horizontal(|ui| {
+                    applied = ui.button(tr("Appliquer")).clicked();
+                    closed = ui.button(tr("Annuler")).clicked();
+                });
+            });
+        if applied && let Some(properties) = self.properties_edit.take() {
+            self.apply_properties(properties);
+        } else if closed {
+            self.properties_edit = None;
+        }
+    }
+
+    // Console du journal : entrées filtrées par niveau et par texte, les plus récentes en bas
+    fn log_window(&mut self, ctx: &egui::Context) {
+        const LEVELS: [(tracing::Level, &str); 5] = [
+            (tracing::Level::ERROR, "Erreurs"),
+            (tracing::Level::WARN, "Avertissements"),
+            (tracing::Level::INFO, "Informations"),
+            (tracing::Level::DEBUG, "Débogage"),
+            (tracing::Level::TRACE, "Trace"),
+        ];
+        let mut open = true;
+        egui::Window::new(tr("📋 Journal"))
+            .open(&mut open)
+            .default_size([640.0, 320.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let selected = LEVELS.iter().find(|(level, _)| *level == self.log_level);
+                    egui::ComboBox::from_id_source("log_level")
+                        .selected_text(tr(selected.map_or("", |(_, name)| name)))
+                        .show_ui(ui, |ui| {
+                            for (level, name) in LEVELS {
+                                ui.selectable_value(&mut self.log_level, level, tr(name));
+                            }
+                        });
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.log_filter)
+                            .hint_text(tr("Filtrer"))
+                            .desired_width(160.0),
+                    );
+                    if ui.button(tr("Effacer")).clicked() {
+                        logging::clear();
+                    }
+                });
+                ui.separator();
+                let visuals = ui.visuals().clone();
+                logging::with_entries(|entries| {
+                    // Plus détaillé = plus grand pour `tracing::Level`
+                    let shown: Vec<&logging::Entry> = entries
+                        .iter()
+                        .filter(|entry| entry.level <= self.log_level)
+                        .filter(|entry| {
+                            entry.text.contains(&self.log_filter)
+                                || entry.target.contains(&self.log_filter)
+                        })
+                        .collect();
+                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false; 2])
+                        .stick_to_bottom(true)
+                        .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                            for entry in &shown[rows] {
+                                let color = match entry.level {
+                                    tracing::Level::ERROR => visuals.error_fg_color,
+                                    tracing::Level::WARN => visuals.warn_fg_color,
+                                    tracing::Level::INFO => visuals.text_color(),
+                                    _ => visuals.weak_text_color(),
+                                };
+                                let text = format!(
+                                    "{:>9.3} {:<5} {} : {}",
+                                    entry.time, entry.level, entry.target, entry.text
+                                );
+                                ui.label(egui::RichText::new(text).monospace().color(color));
+                            }
+                        });
+                });
+            });
+        self.show_log &= open;
     }
-}
\ No newline at end of file
+
+    // Remplace le script de la console par le contenu du fichier `path`
+    fn load_script(&mut self, path: std::path::PathBuf) {
+        match std::fs::read_to_string(&path) {
+            Ok(source) => {
+                self.script_source = source;
+                self.script_output = None;
+            }
+            Err(error) => {
+                let name = path.display().to_string();
+                let text = tr_fill("Script « {} » illisible : {}", &[&name, &error.to_string()]);
+                let retry =
+                    Box::new(move |app: &mut Self, _: &egui::Context| app.load_script(path));
+                self.toasts.error_with_retry(text, retry);
+            }
+        }
+    }
+
+    // Exécute le script de la console avec le pinceau actuel ; ses modifications
+    // forment une seule étape de l'historique
+    fn run_script(&mut self, ctx: &egui::Context) {
+        let brush = Brush {
+            color: self.brush_color,
+            width: self.brush_size,
+            opacity: self.brush_opacity,
+        };
+        let result = script::run(&mut self.document, &self.script_source, brush);
+        self.script_output = Some(result.map(|output| {
+            let mut printed = output.printed;
+            for id in output.commands {
+                // Un script ne relance pas la console, ce qui bouclerait sans fin
+                if id == "script.run" || !self.run_command(ctx, &id) {
+                    printed.push(tr_fill("Commande indisponible : {}", &[&id]));
+                }
+            }
+            printed
+        }));
+    }
+
+    // Liste des objets, du plus récent au plus ancien, puis détails de l'objet choisi
+    fn inspector_ui(&mut self, ui: &mut egui::Ui) {
+        if self.document.is_empty() {
+            ui.label(tr("Aucun objet"));
+            return;
+        }
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_source("objects")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                for object in self.document.objects().iter().rev() {
+                    let meta = object.meta();
+                    let name = meta.name.as_deref().unwrap_or(tr(object.kind_name()));
+                    let selected = self.inspected == Some(meta.id);
+                    if ui
+                        .selectable_label(selected, format!("#{} {name}", meta.id))
+                        .clicked()
+                    {
+                        clicked = Some((!selected).then_some(meta.id));
+                    }
+                }
+            });
+        if let Some(inspected) = clicked {
+            self.inspected = inspected;
+        }
+
+        let Some(index) = self.inspected.and_then(|id| self.document.position(id)) else {
+            return;
+        };
+        let object = &self.document.objects()[index];
+        let (kind, meta) = (object.kind_name(), object.meta().clone());
+        ui.separator();
+        egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+            ui.label(tr("Identifiant"));
+            ui.label(meta.id.to_string());
+            ui.end_row();
+            ui.label(tr("Type"));
+            ui.label(tr(kind));
+            ui.end_row();
+            ui.label(tr("Auteur"));
+            ui.label(if meta.author.is_empty() {
+                "–"
+            } else {
+                &meta.author
+            });
+            ui.end_row();
+            ui.label(tr("Création"));
+            ui.label(format_age(meta.created_at));
+            ui.end_row();
+            ui.label(tr("Nom"));
+            let response = ui.text_edit_singleline(&mut self.name_input);
+            accessible_label(&response, egui::WidgetType::TextEdit, tr("Nom"));
+            if response.lost_focus() {
+                self.document.rename(index, &self.name_input);
+            } else if !response.has_focus() {
+                // Hors saisie, le champ suit le nom (changé par une annulation, par exemple)
+                self.name_input = meta.name.unwrap_or_default();
+            }
+            ui.end_row();
+            ui.label(tr("Verrouillé"));
+            let mut locked = meta.locked;
+            if ui.checkbox(&mut locked, "").changed() {
+                self.document.set_locked(index, locked);
+            }
+            ui.end_row();
+        });
+    }
+
+    // Nombre d'objets et de points, emprise du dessin, taille du fichier enregistré et
+    // répartition par couleur
+    fn statistics_ui(&mut self, ui: &mut egui::Ui) {
+        const LISTED_COLORS: usize = 8;
+        let objects = self.document.objects();
+        let points: usize = objects.iter().map(DrawObject::point_count).sum();
+        let erased = objects.iter().filter(|object| object.is_eraser()).count();
+        let bounds = objects.iter().fold(Rect::NOTHING, |bounds, object| {
+            bounds.union(object.bounds())
+        });
+        let mut colors: Vec<(Color32, usize)> = Vec::new();
+        for object in objects.iter().filter(|object| !object.is_eraser()) {
+            let Some(object_color) = object.color() else {
+                continue;
+            };
+            match colors.iter_mut().find(|(color, _)| *color == object_color) {
+                Some((_, count)) => *count += 1,
+                None => colors.push((object_color, 1)),
+            }
+        }
+        colors.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        // Toute l'animation, encodée comme par « Enregistrer » : seulement après une
+        // modification, pas à chaque image affichée
+        let file_size = match &self.file_size {
+            Some(size) => size.clone(),
+            None => {
+                let size = export::encode_frames(&self.all_frames()).map(|bytes| bytes.len());
+                self.file_size = Some(size.clone());
+                size
+            }
+        };
+
+        egui::Grid::new("statistics").num_columns(2).show(ui, |ui| {
+            ui.label(tr("Objets"));
+            ui.label(tr_fill(
+                "{} (dont {} de gomme)",
+                &[&self.document.len().to_string(), &erased.to_string()],
+            ));
+            ui.end_row();
+            ui.label(tr("Points"));
+            ui.label(points.to_string());
+            ui.end_row();
+            ui.label(tr("Emprise"));
+            if bounds.is_positive() {
+                let size = format!("{:.0} × {:.0}", bounds.width(), bounds.height());
+                ui.label(size).on_hover_text(format!(
+                    "({:.0}, {:.0}) – ({:.0}, {:.0})",
+                    bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+                ));
+            } else {
+                ui.label("–");
+            }
+            ui.end_row();
+            ui.label(tr("Fichier"));
+            match file_size {
+                Ok(size) => ui.label(format_bytes(size)).on_hover_text(tr(
+                    "Taille estimée à l'enregistrement, toutes images comprises",
+                )),
+                Err(error) => ui.label("–").on_hover_text(error),
+            };
+            ui.end_row();
+        });
+
+        if !colors.is_empty() {
+            ui.label(tr("Objets par couleur"));
+            for (color, count) in colors.iter().take(LISTED_COLORS) {
+                ui.horizontal(|ui| {
+                    color_swatch(ui, *color, false);
+                    ui.label(count.to_string());
+                });
+            }
+            if colors.len() > LISTED_COLORS {
+                let others: usize = colors[LISTED_COLORS..].iter().map(|(_, count)| count).sum();
+                ui.label(tr_fill("Autres couleurs : {}", &[&others.to_string()]));
+            }
+        }
+    }
+
+    // Indice de l'outil actif dans `tools` ; l'outil d'une extension absente est remplacé
+    // par la main levée
+    fn tool_index(&self) -> usize {
+        let builtin = BrushMode::ALL.len();
+        match &self.mode {
+            BrushMode::Plugin(name) => self.tools[builtin..]
+                .iter()
+                .position(|tool| tool.name() == name)
+                .map_or(0, |index| builtin + index),
+            mode => BrushMode::ALL.iter().position(|m| m == mode).unwrap_or(0),
+        }
+    }
+
+    // Outils des extensions, avec leur icône et leur nom traduit
+    fn plugin_tools(&self) -> Vec<(BrushMode, &'static str, &'static str)> {
+        self.tools[BrushMode::ALL.len()..]
+            .iter()
+            .map(|tool| {
+                let name = tool.name();
+                (BrushMode::Plugin(name.to_owned()), tool.icon(), tr(name))
+            })
+            .collect()
+    }
+
+    // Pinceau transmis à l'outil actif : la gomme peint la couleur du fond, opaque
+    fn brush(&self) -> Brush {
+        let (color, opacity) = if self.mode == BrushMode::Eraser {
+            (self.canvas_background(), 1.0)
+        } else {
+            (self.brush_color, self.brush_opacity)
+        };
+        Brush {
+            color,
+            width: self.brush_size,
+            opacity,
+        }
+    }
+
+    // Couleur nommée que suivront les nouveaux traits : celle choisie, tant que le pinceau
+    // a encore sa couleur (une couleur libre choisie ensuite l'emporte)
+    fn active_swatch(&self) -> Option<&str> {
+        let name = self.brush_swatch.as_deref()?;
+        let current = self.document.swatch(name) == Some(self.brush_color);
+        (current && self.mode != BrushMode::Eraser).then_some(name)
+    }
+
+    // Couleurs nommées du document : les choisir pour dessiner, les redéfinir (tous les
+    // traits qui les suivent changent, en une annulation), en créer ou en retirer
+    fn named_colors_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr("Couleurs nommées"));
+        let mut picked = None;
+        let mut redefined = None;
+        let mut removed = None;
+        for swatch in self.document.palette() {
+            ui.horizontal(|ui| {
+                let selected = self.brush_swatch.as_deref() == Some(swatch.name.as_str())
+                    && self.brush_color == swatch.color;
+                if color_swatch(ui, swatch.color, selected).clicked() {
+                    picked = Some(swatch.clone());
+                }
+                ui.label(&swatch.name);
+                let redefine = ui
+                    .add_enabled(
+                        swatch.color != self.brush_color,
+                        egui::Button::new("🖌").small(),
+                    )
+                    .on_hover_text(tr("Donner la couleur actuelle à tous ses traits"));
+                let label = tr_fill("Redéfinir {}", &[&swatch.name]);
+                accessible_label(&redefine, egui::WidgetType::Button, &label);
+                if redefine.clicked() {
+                    redefined = Some(swatch.name.clone());
+                }
+                let delete = ui.small_button("🗑").on_hover_text(tr("Supprimer"));
+                let label = tr_fill("Supprimer la couleur {}", &[&swatch.name]);
+                accessible_label(&delete, egui::WidgetType::Button, &label);
+                if delete.clicked() {
+                    removed = Some(swatch.name.clone());
+                }
+            });
+        }
+        if let Some(swatch) = picked {
+            self.brush_color = swatch.color;
+            self.brush_swatch = Some(swatch.name);
+        }
+        if let Some(name) = redefined {
+            self.document.set_swatch(&name, self.brush_color);
+            self.brush_swatch = Some(name);
+        }
+        if let Some(name) = removed {
+            self.document.remove_swatch(&name);
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_swatch_name)
+                    .hint_text(tr("Nom"))
+                    .desired_width(100.0),
+            );
+            let name = self.new_swatch_name.trim().to_owned();
+            let available = !name.is_empty() && self.document.swatch(&name).is_none();
+            let add = ui
+                .add_enabled(available, egui::Button::new("+").small())
+                .on_hover_text(tr("Nommer la couleur actuelle"));
+            accessible_label(
+                &add,
+                egui::WidgetType::Button,
+                tr("Nommer la couleur actuelle"),
+            );
+            if add.clicked() {
+                self.document.set_swatch(&name, self.brush_color);
+                self.brush_swatch = Some(name);
+                self.new_swatch_name.clear();
+            }
+        });
+    }
+
+    // Transmet un événement du pointeur à l'outil actif et applique les actions produites
+    fn tool_event(&mut self, event: PointerEvent) {
+        let brush = self.brush();
+        let index = self.tool_index();
+        let actions = self.tools[index].pointer_event(event, brush, &self.document);
+        let swatch = self.active_swatch().map(str::to_owned);
+        let mut applied = false;
+        for action in actions {
+            // Les traits dessinés avec une couleur nommée la suivront
+            let action = match (action, &swatch) {
+                (Action::Push(object), Some(swatch)) => Action::Push(object.with_swatch(swatch)),
+                (action, _) => action,
+            };
+            // Le pointeur laser n'entre ni dans le dessin, ni dans l'historique
+            if self.mode == BrushMode::Laser {
+                if let Action::Push(object) = action
+                    && let Figure::Stroke(line) = object.figure()
+                {
+                    self.laser_strokes
+                        .push((line.points().to_vec(), Instant::now()));
+                }
+            } else {
+                applied |= self.document.apply(action);
+            }
+        }
+        if applied && self.mode != BrushMode::Eraser {
+            self.remember_color(brush.color);
+        }
+    }
+
+    // Ajoute une position au geste en cours, ou le commence
+    fn extend_current_line(&mut self, pos: Pos2) {
+        if self.drawing {
+            self.tool_event(PointerEvent::Move(pos));
+        } else {
+            self.drawing = true;
+            self.tool_event(PointerEvent::Press(pos));
+        }
+    }
+
+    // Termine le geste : l'outil ajoute ses traits au dessin
+    fn finish_line(&mut self) {
+        self.drawing = false;
+        self.tool_event(PointerEvent::Release);
+    }
+
+    // Abandonne le geste en cours sans rien ajouter au dessin
+    fn cancel_gesture(&mut self) {
+        self.drawing = false;
+        let index = self.tool_index();
+        self.tools[index].cancel();
+    }
+
+    // Mode accessibilité : flèches pour déplacer le curseur (Maj pour aller plus vite),
+    // Entrée pour poser ou lever le crayon, Échap pour abandonner le trait en cours
+    fn keyboard_input(&mut self, ctx: &egui::Context, area: Rect) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let cursor = *self.keyboard_cursor.get_or_insert(area.center());
+        let (delta, toggle_pen, cancel) = ctx.input(|i| {
+            let step = if i.modifiers.shift { 25.0 } else { 5.0 };
+            let mut delta = egui::Vec2::ZERO;
+            for (key, direction) in [
+                (egui::Key::ArrowLeft, egui::vec2(-1.0, 0.0)),
+                (egui::Key::ArrowRight, egui::vec2(1.0, 0.0)),
+                (egui::Key::ArrowUp, egui::vec2(0.0, -1.0)),
+                (egui::Key::ArrowDown, egui::vec2(0.0, 1.0)),
+            ] {
+                if i.key_pressed(key) {
+                    delta += direction * step;
+                }
+            }
+            (
+                delta,
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+
+        let cursor = area.clamp(cursor + delta);
+        self.keyboard_cursor = Some(cursor);
+        if cancel && self.keyboard_pen_down {
+            self.cancel_gesture();
+            self.keyboard_pen_down = false;
+        }
+        let point = self.to_drawing(cursor);
+        if toggle_pen {
+            if self.keyboard_pen_down {
+                self.extend_current_line(point);
+                self.finish_line();
+            } else {
+                self.cancel_gesture();
+                self.extend_current_line(point);
+            }
+            self.keyboard_pen_down = !self.keyboard_pen_down;
+        } else if self.keyboard_pen_down && delta != egui::Vec2::ZERO {
+            self.extend_current_line(point);
+        }
+    }
+
+    // Supprime tous les traits (annulable)
+    fn clear_all(&mut self) {
+        self.document.clear();
+    }
+
+    // « Effacer tout » depuis l'interface : confirmation si le dessin est important
+    fn request_clear(&mut self) {
+        if self.prefs.confirm_clear && self.document.len() >= CONFIRM_CLEAR_LINES {
+            self.confirm_clear_open = true;
+        } else {
+            self.clear_all();
+        }
+    }
+
+    // Retient le point du clic droit et l'objet qui s'y trouve, cibles du menu contextuel
+    fn set_context_target(&mut self, ctx: &egui::Context, pos: Pos2) {
+        let point = self.to_drawing(pos);
+        let index = self.object_at(ctx, point);
+        let id = index.map(|index| self.document.objects()[index].meta().id);
+        self.context_target = Some((point, id));
+    }
+
+    // Indice de l'objet sous le clic droit, s'il est encore dans le dessin
+    fn context_object(&self) -> Option<usize> {
+        let (_, id) = self.context_target?;
+        self.document.position(id?)
+    }
+
+    // Objet sous le clic droit, s'il n'est pas verrouillé
+    fn unlocked_context_object(&self) -> Option<usize> {
+        self.context_object()
+            .filter(|&index| !self.document.objects()[index].meta().locked)
+    }
+
+    // Copie l'objet sous le clic droit
+    fn copy_context_object(&mut self) {
+        if let Some(index) = self.context_object() {
+            self.clipboard = Some(self.document.objects()[index].clone());
+        }
+    }
+
+    // Colle la copie centrée sur le point du clic droit
+    fn paste_here(&mut self) {
+        if let (Some(object), Some((point, _))) = (&self.clipboard, self.context_target) {
+            let copy = object.duplicate(point - object.bounds().center());
+            self.document.apply(Action::Push(copy));
+        }
+    }
+
+    // Passe l'objet sous le clic droit au-dessus des autres
+    fn bring_to_front(&mut self) {
+        if let Some(index) = self.unlocked_context_object() {
+            let object = self.document.objects()[index].clone();
+            let last = self.document.len() - 1;
+            self.document.apply(Action::Batch(vec![
+                Action::Remove(vec![index]),
+                Action::Insert(vec![(last, object)]),
+            ]));
+        }
+    }
+
+    // Donne la couleur du pinceau à l'objet sous le clic droit ; avec une couleur nommée,
+    // l'objet la suivra comme un trait dessiné avec
+    fn apply_brush_color(&mut self) {
+        if let Some(index) = self.unlocked_context_object() {
+            let recolored = self.document.objects()[index].recolored(self.brush_color);
+            let recolored = match self.active_swatch() {
+                Some(swatch) => recolored.with_swatch(swatch),
+                None => recolored,
+            };
+            self.document.apply(Action::Batch(vec![
+                Action::Remove(vec![index]),
+                Action::Insert(vec![(index, recolored)]),
+            ]));
+        }
+    }
+
+    // Supprime l'objet sous le clic droit
+    fn delete_context_object(&mut self) {
+        if let Some(index) = self.unlocked_context_object() {
+            self.document.apply(Action::Remove(vec![index]));
+        }
+    }
+
+    // Verrouille ou déverrouille l'objet sous le clic droit
+    fn lock_context_object(&mut self, locked: bool) {
+        if let Some(index) = self.context_object() {
+            self.document.set_locked(index, locked);
+        }
+    }
+
+    // Menu contextuel du canevas (clic droit) : actions sur l'objet visé s'il y en a un,
+    // collage, historique et outils
+    fn canvas_context_menu(&mut self, ui: &mut egui::Ui) {
+        if let Some(index) = self.context_object() {
+            let lock = if self.document.objects()[index].meta().locked {
+                "object.unlock"
+            } else {
+                "object.lock"
+            };
+            for id in [
+                "object.copy",
+                "object.bring_to_front",
+                "object.apply_color",
+                lock,
+                "object.delete",
+            ] {
+                self.command_menu_item(ui, id);
+            }
+        }
+        self.command_menu_item(ui, "object.paste_here");
+        ui.separator();
+        self.command_menu_item(ui, "edit.undo");
+        self.command_menu_item(ui, "edit.redo");
+        ui.separator();
+        for id in [
+            "tool.freehand",
+            "tool.line",
+            "tool.rectangle",
+            "tool.ellipse",
+            "tool.eraser",
+            "tool.laser",
+        ] {
+            self.command_menu_item(ui, id);
+        }
+        for (mode, icon, name) in self.plugin_tools() {
+            if ui
+                .selectable_label(self.mode == mode, format!("{icon} {name}"))
+                .clicked()
+            {
+                self.mode = mode;
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        self.command_menu_item(ui, "edit.clear");
+    }
+
+    // Exécute la commande `id` si elle est disponible ; retourne `false` sinon
+    fn run_command(&mut self, ctx: &egui::Context, id: &str) -> bool {
+        match commands::find(id) {
+            Some(command) if (command.enabled)(self) => {
+                (command.run)(self, ctx);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Choisit un outil, par sa commande pour les outils intégrés
+    fn select_tool(&mut self, ctx: &egui::Context, mode: BrushMode) {
+        match mode.command() {
+            Some(command) => (command.run)(self, ctx),
+            None => self.mode = mode,
+        }
+    }
+
+    // Bouton-icône d'une commande, grisé quand elle est indisponible
+    fn command_button(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(command) = commands::find(id) else {
+            return;
+        };
+        let ctx = ui.ctx().clone();
+        let (icon, _) = command.icon_and_name();
+        let enabled = (command.enabled)(self);
+        if ui
+            .add_enabled_ui(enabled, |ui| {
+                icon_button(ui, icon, &command.hint(&ctx, &self.prefs.shortcuts))
+            })
+            .inner
+            .clicked()
+        {
+            (command.run)(self, &ctx);
+        }
+    }
+
+    // Entrée de menu d'une commande, avec son raccourci
+    fn command_menu_item(&mut self, ui: &mut egui::Ui, id: &str) {
+        let Some(command) = commands::find(id) else {
+            return;
+        };
+        let ctx = ui.ctx().clone();
+        let mut button = egui::Button::new(tr(command.label));
+        if let Some(shortcut) = command.shortcut(&self.prefs.shortcuts) {
+            button = button.shortcut_text(ctx.format_shortcut(&shortcut));
+        }
+        if ui.add_enabled((command.enabled)(self), button).clicked() {
+            (command.run)(self, &ctx);
+            ui.close_menu();
+        }
+    }
+
+    // Raccourci de la commande `id` tel qu'affiché, vide si elle n'en a pas
+    fn shortcut_text(&self, ctx: &egui::Context, id: &str) -> String {
+        commands::find(id)
+            .and_then(|command| command.shortcut(&self.prefs.shortcuts))
+            .map_or_else(String::new, |shortcut| ctx.format_shortcut(&shortcut))
+    }
+
+    // Raccourcis des commandes disponibles ; pendant la saisie de texte, seuls ceux avec Ctrl
+    // restent actifs, les autres touches allant au champ de texte
+    fn command_shortcuts(&mut self, ctx: &egui::Context) {
+        // La touche attendue par l'éditeur de raccourcis ne déclenche rien
+        if self.recording_shortcut.is_some() {
+            return;
+        }
+        let typing = ctx.wants_keyboard_input();
+        for command in commands::COMMANDS {
+            if let Some(shortcut) = command.shortcut(&self.prefs.shortcuts)
+                && (!typing || shortcut.modifiers.command)
+                && (command.enabled)(self)
+                && ctx.input_mut(|i| i.consume_shortcut(&shortcut))
+            {
+                (command.run)(self, ctx);
+            }
+        }
+    }
+
+    // Raccourcis de toutes les commandes : un clic sur un raccourci attend la touche qui le
+    // remplace (Échap pour annuler), qu'une autre commande ne peut alors plus garder
+    fn shortcuts_ui(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        if let Some(id) = self.recording_shortcut {
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(egui::KeyboardShortcut::new(*modifiers, *key)),
+                    _ => None,
+                })
+            });
+            match pressed {
+                Some(shortcut) if shortcut.key == egui::Key::Escape => {
+                    self.recording_shortcut = None;
+                }
+                Some(shortcut) => {
+                    for command in commands::COMMANDS {
+                        if command.shortcut(&self.prefs.shortcuts) == Some(shortcut) {
+                            self.prefs.shortcuts.insert(command.id.to_owned(), None);
+                        }
+                    }
+                    let binding = (shortcut.modifiers, shortcut.key);
+                    self.prefs.shortcuts.insert(id.to_owned(), Some(binding));
+                    self.recording_shortcut = None;
+                }
+                None => {}
+            }
+        }
+        egui::Grid::new("shortcuts").num_columns(3).show(ui, |ui| {
+            for command in commands::COMMANDS {
+                ui.label(tr(command.label));
+                let shortcut = command.shortcut(&self.prefs.shortcuts);
+                let text = if self.recording_shortcut == Some(command.id) {
+                    tr("Appuyez sur une touche…").to_owned()
+                } else {
+                    shortcut
+                        .map_or_else(|| "–".to_owned(), |shortcut| ctx.format_shortcut(&shortcut))
+                };
+                if ui
+                    .button(text)
+                    .on_hover_text(tr("Changer le raccourci"))
+                    .clicked()
+                {
+                    self.recording_shortcut = Some(command.id);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled_ui(shortcut.is_some(), |ui| {
+                            icon_button(ui, "✖", tr("Retirer le raccourci"))
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        self.prefs.shortcuts.insert(command.id.to_owned(), None);
+                    }
+                    if ui
+                        .add_enabled_ui(shortcut != command.default_shortcut, |ui| {
+                            icon_button(ui, "↺", tr("Raccourci par défaut"))
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        self.prefs.shortcuts.remove(command.id);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    }
+
+    // Entre ou sort du mode plein écran sans panneaux
+    fn set_focus_mode(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.focus_mode = enabled;
+        if !enabled {
+            self.presentation = None;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(enabled));
+    }
+
+    // Le mode présentation passe en plein écran et cache d'abord tout le dessin
+    fn set_presentation(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.presentation = enabled.then_some(0);
+        self.set_focus_mode(ctx, enabled);
+    }
+
+    // Étapes de la présentation : une étape regroupe les traits consécutifs de même
+    // couleur (et de même outil), pour révéler un élément du schéma d'un coup
+    fn same_group(a: &DrawObject, b: &DrawObject) -> bool {
+        a.color() == b.color() && a.is_eraser() == b.is_eraser()
+    }
+
+    fn next_step(&self, shown: usize) -> usize {
+        let Some(first) = self.document.objects().get(shown) else {
+            return self.document.len();
+        };
+        shown
+            + self.document.objects()[shown..]
+                .iter()
+                .take_while(|object| Self::same_group(first, object))
+                .count()
+    }
+
+    fn previous_step(&self, shown: usize) -> usize {
+        let shown = shown.min(self.document.len());
+        let Some(last) = shown
+            .checked_sub(1)
+            .map(|index| &self.document.objects()[index])
+        else {
+            return 0;
+        };
+        shown
+            - self.document.objects()[..shown]
+                .iter()
+                .rev()
+                .take_while(|object| Self::same_group(last, object))
+                .count()
+    }
+
+    // Numéro de l'étape atteinte et nombre total d'étapes
+    fn step_count(&self, shown: usize) -> (usize, usize) {
+        let mut index = 0;
+        let (mut current, mut total) = (0, 0);
+        while index < self.document.len() {
+            index = self.next_step(index);
+            total += 1;
+            if index <= shown {
+                current = total;
+            }
+        }
+        (current, total)
+    }
+
+    // Conversions entre l'écran et le dessin
+    fn to_drawing(&self, pos: Pos2) -> Pos2 {
+        ((pos - self.pan).to_vec2() / self.zoom).to_pos2()
+    }
+
+    fn to_screen(&self, pos: Pos2) -> Pos2 {
+        (pos.to_vec2() * self.zoom).to_pos2() + self.pan
+    }
+
+    // Change le zoom en gardant immobile le point `anchor` de l'écran
+    fn zoom_at(&mut self, zoom: f32, anchor: Pos2) {
+        let fixed = self.to_drawing(anchor);
+        self.zoom = zoom.clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        self.pan = anchor.to_vec2() - fixed.to_vec2() * self.zoom;
+    }
+
+    // Commandes de zoom de la barre d'état, centrées sur le canevas
+    fn zoom_controls(&mut self, ui: &mut egui::Ui) {
+        let center = self.canvas_rect.center();
+        ui.menu_button("⏷", |ui| {
+            for level in ZOOM_LEVELS {
+                let selected = (self.zoom - level).abs() < 1e-3;
+                if ui
+                    .selectable_label(selected, format!("{:.0} %", level * 100.0))
+                    .clicked()
+                {
+                    self.zoom_at(level, center);
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_hover_text(tr("Niveaux de zoom"));
+        self.command_button(ui, "view.zoom_in");
+        let mut percent = self.zoom * 100.0;
+        let field = ui.add(
+            egui::DragValue::new(&mut percent)
+                .suffix(" %")
+                .max_decimals(0)
+                .clamp_range(ZOOM_RANGE.start() * 100.0..=ZOOM_RANGE.end() * 100.0),
+        );
+        accessible_label(&field, egui::WidgetType::DragValue, tr("Zoom"));
+        if field.changed() {
+            self.zoom_at(percent / 100.0, center);
+        }
+        self.command_button(ui, "view.zoom_out");
+    }
+
+    // Force la texture à être entièrement recalculée au prochain rendu
+    fn invalidate_canvas(&mut self) {
+        self.baked_lines = usize::MAX;
+    }
+
+    // Met à jour la texture des traits validés : les nouveaux traits sont ajoutés
+    // par zones, tout le reste (annulation, redimensionnement, échelle) refait l'image
+    fn bake_canvas(&mut self, ctx: &egui::Context, rect: Rect) {
+        let pixels_per_point = ctx.pixels_per_point();
+        // Les maillages sont en unités du dessin : l'anticrénelage dépend aussi du zoom
+        let scale = pixels_per_point * self.zoom;
+        if self.mesh_pixels_per_point != scale {
+            self.document.clear_meshes();
+            self.mesh_pixels_per_point = scale;
+            self.invalidate_canvas();
+        }
+        // Un trait déjà rastérisé a changé (annulation, suppression) : on refait l'image
+        if let Some(index) = self.document.take_changed_from()
+            && index < self.baked_lines
+        {
+            self.invalidate_canvas();
+        }
+        // En présentation, seuls les traits déjà révélés sont rastérisés
+        let shown = self
+            .presentation
+            .map_or(self.document.len(), |shown| shown.min(self.document.len()));
+        let size = [
+            (rect.width() * pixels_per_point).round() as usize,
+            (rect.height() * pixels_per_point).round() as usize,
+        ];
+        // Pendant un déplacement de la vue, l'ancienne texture est simplement décalée
+        let full_rebake = self.canvas.is_none()
+            || rect != self.canvas_rect
+            || self.baked_lines > shown
+            || (self.pan != self.baked_pan && !self.panning);
+        if !full_rebake && self.baked_lines == shown {
+            return;
+        }
+        let start = Instant::now();
+        self.stats.baked_triangles = 0;
+
+        // Une fois la texture envoyée, l'image n'est plus partagée : on la modifie sans la copier
+        let image = Arc::make_mut(&mut self.canvas_image);
+        if full_rebake {
+            if image.size == size {
+                image.pixels.fill(Color32::TRANSPARENT);
+            } else {
+                *image = ColorImage::new(size, Color32::TRANSPARENT);
+            }
+            self.canvas_rect = rect;
+            self.baked_lines = 0;
+            self.baked_pan = self.pan;
+        }
+
+        // Coin de la texture et zone visible, en coordonnées du dessin
+        let origin = ((rect.min - self.baked_pan).to_vec2() / self.zoom).to_pos2();
+        let visible_area = Rect::from_min_size(origin, rect.size() / self.zoom);
+        let mut tessellator = Tessellator::new(
+            scale,
+            ctx.tessellation_options(|options| *options),
+            ctx.fonts(|fonts| fonts.font_image_size()),
+            Vec::new(),
+        );
+        let mut dirty = Rect::NOTHING;
+        // L'index du document écarte d'emblée les objets hors de la zone visible
+        let mut pending = self.document.objects_in(visible_area);
+        pending.retain(|index| (self.baked_lines..shown).contains(index));
+        let objects = self.document.objects_mut();
+        for index in pending {
+            for line in objects[index].lines_mut() {
+                if line.points().len() < 2 || !line.rect().intersects(visible_area) {
+                    continue;
+                }
+                let blend = if line.is_eraser() {
+                    raster::Blend::Erase
+                } else {
+                    raster::Blend::Over
+                };
+                let mesh = line.mesh(&mut tessellator);
+                self.stats.baked_triangles += mesh.indices.len() / 3;
+                if let Some(region) = raster::draw_mesh(image, mesh, origin, scale, blend) {
+                    dirty = dirty.union(region);
+                }
+            }
+        }
+        self.baked_lines = shown;
+        self.stats.bake_time = start.elapsed();
+
+        match &mut self.canvas {
+            Some(canvas) if !full_rebake => {
+                if dirty.is_positive() {
+                    let position = [dirty.min.x as usize, dirty.min.y as usize];
+                    let region = image.region(&dirty, None);
+                    canvas.set_partial(position, region, TextureOptions::NEAREST);
+                }
+            }
+            Some(canvas) => canvas.set(self.canvas_image.clone(), TextureOptions::NEAREST),
+            None => {
+                self.canvas = Some(ctx.load_texture(
+                    "canvas",
+                    self.canvas_image.clone(),
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+    }
+}
+
+impl eframe::App for PaintApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, PREFERENCES_KEY, &self.prefs);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // --- Modifications du document, quelle qu'en soit l'origine ---
+        self.document_changes();
+
+        // --- Fermeture : le dessin n'est pas enregistré, on demande confirmation ---
+        if ctx.input(|i| i.viewport().close_requested()) && self.dirty && !self.close_allowed {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.confirm_close_open = true;
+        }
+
+        // --- Titre de la fenêtre : titre ou nom du document, suivi de * s'il a été modifié ---
+        let title = &self.document.properties().title;
+        let file_name = self
+            .file_path
+            .as_deref()
+            .and_then(std::path::Path::file_name);
+        let name = match file_name {
+            _ if !title.is_empty() => title.clone(),
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => format!("{}.rpaint", tr("sans-titre")),
+        };
+        let title = format!("{}{name} — {APP_NAME}", if self.dirty { "*" } else { "" });
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+
+        // --- Échelle de l'interface : Ctrl+plus / Ctrl+moins d'egui la modifient aussi ---
+        let zoom = ctx.zoom_factor();
+        if zoom != self.prefs.ui_scale {
+            self.prefs.ui_scale = zoom;
+            self.ui_scale_input = zoom;
+        }
+
+        // --- Gestion des raccourcis clavier ---
+        self.command_shortcuts(ctx);
+
+        // --- Présentation : les flèches révèlent le dessin étape par étape ---
+        if let Some(shown) = self.presentation {
+            let (next, previous, first, last, exit) = ctx.input(|i| {
+                let any = |keys: &[egui::Key]| keys.iter().any(|key| i.key_pressed(*key));
+                (
+                    any(&[
+                        egui::Key::ArrowRight,
+                        egui::Key::ArrowDown,
+                        egui::Key::PageDown,
+                    ]),
+                    any(&[egui::Key::ArrowLeft, egui::Key::ArrowUp, egui::Key::PageUp]),
+                    i.key_pressed(egui::Key::Home),
+                    i.key_pressed(egui::Key::End),
+                    i.key_pressed(egui::Key::Escape),
+                )
+            });
+            if exit {
+                self.set_presentation(ctx, false);
+            } else if next {
+                self.presentation = Some(self.next_step(shown));
+            } else if previous {
+                self.presentation = Some(self.previous_step(shown));
+            } else if first {
+                self.presentation = Some(0);
+            } else if last {
+                self.presentation = Some(self.document.len());
+            }
+        }
+        if !ctx.wants_keyboard_input() {
+            let pressed = ctx.input(|i| PRESET_KEYS.iter().position(|key| i.key_pressed(*key)));
+            if let Some(index) = pressed {
+                self.apply_preset(index);
+            }
+        }
+
+        // --- Image de référence déposée sur la fenêtre ---
+        let dropped = ctx.input(|i| i.raw.dropped_files.first().cloned());
+        if let Some(file) = dropped {
+            self.load_reference(ctx, &file);
+        }
+
+        // --- Lecture de l'animation ---
+        if self.playing && self.frames.len() > 1 {
+            let now = ctx.input(|i| i.time);
+            if now >= self.next_frame_time {
+                self.step_frame(true);
+                self.next_frame_time = now + 1.0 / self.fps as f64;
+            }
+            let wait = (self.next_frame_time - now).max(0.0);
+            ctx.request_repaint_after(Duration::from_secs_f64(wait));
+        }
+
+        // --- UI : Panneau de réglages (repliable pour agrandir le canevas) ---
+        if !self.focus_mode {
+            // Les flèches pointent vers le canevas pour afficher, vers le bord pour masquer
+            let (side, show_icon, hide_icon) = if self.prefs.panel_right {
+                (egui::panel::Side::Right, "◀", "▶")
+            } else {
+                (egui::panel::Side::Left, "▶", "◀")
+            };
+            egui::SidePanel::show_animated_between(
+                ctx,
+                self.panel_expanded,
+                egui::SidePanel::new(side, "settings_collapsed")
+                    .resizable(false)
+                    .exact_width(32.0),
+                egui::SidePanel::new(side, "settings"),
+                |ui, how_expanded| {
+                    if how_expanded < 1.0 {
+                        if icon_button(ui, show_icon, tr("Afficher le panneau")).clicked() {
+                            self.panel_expanded = true;
+                        }
+                        return;
+                    }
+                    // Bouton de repli côté canevas, titre côté bord de la fenêtre
+                    ui.horizontal(|ui| {
+                        let end = egui::Layout::right_to_left(egui::Align::Center);
+                        if self.prefs.panel_right {
+                            if icon_button(ui, hide_icon, tr("Masquer le panneau")).clicked() {
+                                self.panel_expanded = false;
+                            }
+                            ui.with_layout(end, |ui| ui.heading("Rust Paint"));
+                        } else {
+                            ui.heading("Rust Paint");
+                            ui.with_layout(end, |ui| {
+                                if icon_button(ui, hide_icon, tr("Masquer le panneau")).clicked() {
+                                    self.panel_expanded = false;
+                                }
+                            });
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical().show(ui, |ui| self.settings_ui(ui));
+                },
+            );
+        }
+
+        // --- Barre d'état : mémoire utilisée ---
+        egui::TopBottomPanel::bottom("status").show_animated(ctx, !self.focus_mode, |ui| {
+            let (drawing_size, history_size) = self.memory_usage();
+            ui.horizontal(|ui| {
+                ui.label(tr_fill(
+                    "Mémoire : {} (dessin {}, historique {})",
+                    &[
+                        &format_bytes(drawing_size + history_size),
+                        &format_bytes(drawing_size),
+                        &format_bytes(history_size),
+                    ],
+                ));
+
+                let budget = self.prefs.memory_budget_mb * 1024.0 * 1024.0;
+                if (drawing_size + history_size) as f32 > budget {
+                    let warning = tr("⚠ Budget mémoire dépassé");
+                    ui.colored_label(ui.visuals().warn_fg_color, warning);
+                    if history_size > 0
+                        && ui
+                            .button(tr("Vider l'historique"))
+                            .on_hover_text(tr(
+                                "Oublie les modifications de toutes les images, qui ne pourront \
+                                 plus être annulées ni rétablies",
+                            ))
+                            .clicked()
+                    {
+                        self.run_command(ctx, "edit.clear_history");
+                    }
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.zoom_controls(ui);
+                    ui.separator();
+                    self.command_button(ui, "view.presentation");
+                });
+            });
+        });
+
+        // --- Zone de dessin ---
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let (response, painter) =
+                ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+            accessible_label(&response, egui::WidgetType::Other, tr("Zone de dessin"));
+            if response.secondary_clicked()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                self.set_context_target(ctx, pos);
+            }
+            // La cible du clic droit ne sert qu'au menu : hors du menu, les commandes
+            // d'objet sont indisponibles
+            let mut menu_open = false;
+            let response = response.context_menu(|ui| {
+                menu_open = true;
+                self.canvas_context_menu(ui);
+            });
+            if !menu_open {
+                self.context_target = None;
+            }
+
+            // 1. Gestion des entrées
+            if self.keyboard_mode && self.presentation.is_none() {
+                self.keyboard_input(ctx, response.rect);
+            }
+            self.panning =
+                !ctx.wants_keyboard_input() && ctx.input(|i| i.key_down(egui::Key::Space));
+            // Alt maintenue : pipette temporaire, sans quitter l'outil de dessin
+            let sampling = !self.panning && ctx.input(|i| i.modifiers.alt);
+            // Ctrl maintenue : déplacement de l'image de référence, si elle n'est pas verrouillée
+            let moving_reference = !self.panning
+                && !sampling
+                && ctx.input(|i| i.modifiers.command)
+                && self
+                    .reference
+                    .as_ref()
+                    .is_some_and(|reference| reference.visible && !reference.locked);
+            if self.panning && (response.hovered() || response.dragged()) {
+                ctx.set_cursor_icon(if response.dragged() {
+                    egui::CursorIcon::Grabbing
+                } else {
+                    egui::CursorIcon::Grab
+                });
+            } else if sampling && response.hovered() {
+                ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+            } else if moving_reference && (response.hovered() || response.dragged()) {
+                ctx.set_cursor_icon(egui::CursorIcon::Move);
+            }
+            if sampling
+                && response.clicked()
+                && let Some(pointer_pos) = response.interact_pointer_pos()
+            {
+                self.pick_color(ctx, self.to_drawing(pointer_pos));
+            }
+            if let Some(pointer_pos) = response.interact_pointer_pos() {
+                if response.dragged_by(egui::PointerButton::Primary) {
+                    if self.panning {
+                        self.pan += response.drag_delta();
+                    } else if moving_reference {
+                        if let Some(reference) = &mut self.reference {
+                            reference.rect =
+                                reference.rect.translate(response.drag_delta() / self.zoom);
+                        }
+                    } else if sampling {
+                        // Alt pendant le geste : le trait en cours est terminé, pas abandonné,
+                        // et la pipette suit le pointeur ; le bouton toujours enfoncé, le
+                        // dessin reprend dans la couleur prise dès qu'Alt est relâchée
+                        if self.drawing && !self.keyboard_pen_down {
+                            self.finish_line();
+                        }
+                        self.pick_color(ctx, self.to_drawing(pointer_pos));
+                    } else if self.presentation.is_none() || self.mode == BrushMode::Laser {
+                        // En présentation, seul le pointeur laser reste actif
+                        self.extend_current_line(self.to_drawing(pointer_pos));
+                    }
+                }
+            } else if self.drawing && !self.keyboard_pen_down {
+                self.finish_line();
+            }
+            // Ctrl + molette ou pincement : zoom autour du pointeur
+            let zoom_delta = ctx.input(|i| i.zoom_delta());
+            if zoom_delta != 1.0
+                && let Some(hover) = response.hover_pos()
+            {
+                self.zoom_at(self.zoom * zoom_delta, hover);
+            }
+
+            // 2. Rendu : Fond, puis historique (texture mise à jour seulement si modifiée)
+            painter.rect_filled(response.rect, 0.0, self.canvas_background());
+            self.bake_canvas(ctx, response.rect);
+            self.stats.shapes = 0;
+            self.draw_reference(&painter, false);
+            if let Some(canvas) = &self.canvas {
+                self.stats.shapes += 1;
+                let [width, height] = self.canvas_image.size;
+                let image_size = egui::vec2(width as f32, height as f32) / ctx.pixels_per_point();
+                painter.image(
+                    canvas.id(),
+                    Rect::from_min_size(
+                        self.canvas_rect.min + self.pan - self.baked_pan,
+                        image_size,
+                    ),
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+
+            self.draw_reference(&painter, true);
+
+            // Cadre du format du dessin
+            if let Some(page) = self.document.page() {
+                let frame = Rect::from_min_max(self.to_screen(page.min), self.to_screen(page.max));
+                self.stats.shapes += 1;
+                painter.rect_stroke(frame, 0.0, ui.visuals().widgets.noninteractive.bg_stroke);
+            }
+
+            // Objet choisi dans l'inspecteur : cadre autour de son emprise
+            if self.presentation.is_none()
+                && let Some(index) = self.inspected.and_then(|id| self.document.position(id))
+            {
+                let bounds = self.document.objects()[index].bounds();
+                let frame =
+                    Rect::from_min_max(self.to_screen(bounds.min), self.to_screen(bounds.max));
+                self.stats.shapes += 1;
+                painter.rect_stroke(frame, 0.0, ui.visuals().selection.stroke);
+            }
+
+            // 3. Rendu : Prévisualisation du geste de l'outil actif et traits du pointeur
+            // laser. Leurs points passent par `overlay_path` et tous forment un seul maillage
+            let mut tessellator = Tessellator::new(
+                ctx.pixels_per_point(),
+                ctx.tessellation_options(|options| *options),
+                ctx.fonts(|fonts| fonts.font_image_size()),
+                Vec::new(),
+            );
+            let mut overlay = Mesh::default();
+            let (zoom, pan) = (self.zoom, self.pan);
+            let to_screen = |p: &Pos2| (p.to_vec2() * zoom).to_pos2() + pan;
+            for line in self.tools[self.tool_index()].preview(self.brush()) {
+                let path = &mut self.overlay_path;
+                path.points.clear();
+                path.points.extend(line.points().iter().map(to_screen));
+                path.closed = line.is_closed();
+                if self.mode == BrushMode::Laser {
+                    draw_laser(&mut tessellator, path, 1.0, &mut overlay);
+                } else {
+                    let width = line.width() * zoom;
+                    if line.is_eraser() {
+                        // Contour aux couleurs du thème, pour distinguer la gomme du fond
+                        path.stroke = Stroke::new(width + 2.0, ui.visuals().weak_text_color());
+                        tessellator.tessellate_path(path, &mut overlay);
+                    }
+                    path.stroke = Stroke::new(width, line.color().gamma_multiply(line.opacity()));
+                    tessellator.tessellate_path(path, &mut overlay);
+                }
+            }
+
+            // Traits du pointeur laser : visibles, puis de plus en plus transparents
+            self.laser_strokes
+                .retain(|(_, end)| end.elapsed().as_secs_f32() < LASER_HOLD + LASER_FADE);
+            for (points, end) in &self.laser_strokes {
+                let fade = (end.elapsed().as_secs_f32() - LASER_HOLD) / LASER_FADE;
+                let alpha = 1.0 - fade.clamp(0.0, 1.0);
+                let path = &mut self.overlay_path;
+                path.points.clear();
+                path.points.extend(points.iter().map(to_screen));
+                path.closed = false;
+                draw_laser(&mut tessellator, path, alpha, &mut overlay);
+            }
+            if !overlay.is_empty() {
+                self.stats.shapes += 1;
+                painter.add(egui::Shape::mesh(overlay));
+            }
+            if !self.laser_strokes.is_empty() {
+                ctx.request_repaint();
+            }
+
+            // 4. Rendu : Curseur du dessin au clavier
+            if self.keyboard_mode
+                && let Some(cursor) = self.keyboard_cursor
+            {
+                let stroke = ui.visuals().selection.stroke;
+                let radius = (self.brush_size * self.zoom / 2.0).max(6.0);
+                self.stats.shapes += 3;
+                painter.circle_stroke(cursor, radius, stroke);
+                painter.hline(
+                    cursor.x - radius - 4.0..=cursor.x + radius + 4.0,
+                    cursor.y,
+                    stroke,
+                );
+                painter.vline(
+                    cursor.x,
+                    cursor.y - radius - 4.0..=cursor.y + radius + 4.0,
+                    stroke,
+                );
+            }
+
+            // 5. Rendu : Curseur de l'outil, à la place de la flèche du système
+            if let Some(hover) = response.hover_pos()
+                && !self.panning
+                && !sampling
+                && !moving_reference
+            {
+                match self.mode {
+                    BrushMode::StraightLine
+                    | BrushMode::Rectangle
+                    | BrushMode::Ellipse
+                    | BrushMode::Plugin(_) => {
+                        ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+                    }
+                    BrushMode::Laser => {
+                        ctx.set_cursor_icon(egui::CursorIcon::None);
+                        self.stats.shapes += 2;
+                        painter.circle_filled(
+                            hover,
+                            LASER_WIDTH * 2.0,
+                            LASER_COLOR.gamma_multiply(0.25),
+                        );
+                        painter.circle_filled(hover, LASER_WIDTH / 2.0 + 1.0, LASER_COLOR);
+                    }
+                    BrushMode::Freehand | BrushMode::Eraser => {
+                        // Contour blanc et noir : visible quels que soient le fond et les traits
+                        let radius = self.brush_size * self.zoom / 2.0;
+                        ctx.set_cursor_icon(egui::CursorIcon::None);
+                        self.stats.shapes += 2;
+                        painter.circle_stroke(
+                            hover,
+                            radius + 1.0,
+                            Stroke::new(1.0, Color32::BLACK),
+                        );
+                        painter.circle_stroke(hover, radius, Stroke::new(1.0, Color32::WHITE));
+                        if radius < 3.0 {
+                            self.stats.shapes += 2;
+                            let stroke = Stroke::new(1.0, Color32::GRAY);
+                            painter.hline(hover.x - 6.0..=hover.x + 6.0, hover.y, stroke);
+                            painter.vline(hover.x, hover.y - 6.0..=hover.y + 6.0, stroke);
+                        }
+                    }
+                }
+            }
+        });
+
+        // --- Écran d'accueil : réglages du nouveau document ---
+        if self.welcome_open {
+            egui::Window::new(tr("Bienvenue dans Rust Paint"))
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.heading(tr("Nouveau document"));
+                    ui.label(tr("Fond du canevas"));
+                    let background = &mut self.new_properties.background;
+                    background_picker(ui, background.get_or_insert(self.prefs.canvas_background));
+                    size_picker(ui, &mut self.new_properties.size);
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("✏ Commencer à dessiner")).clicked() {
+                            let properties = std::mem::take(&mut self.new_properties);
+                            self.new_document(properties);
+                            self.welcome_open = false;
+                        }
+                        ui.checkbox(&mut self.prefs.show_welcome, tr("Afficher au démarrage"));
+                    });
+                    if !self.prefs.recent_files.is_empty() {
+                        ui.separator();
+                        ui.heading(tr("Documents récents"));
+                        let mut opened = None;
+                        for path in &self.prefs.recent_files {
+                            let name = path.file_name().map_or_else(
+                                || path.display().to_string(),
+                                |name| name.to_string_lossy().into_owned(),
+                            );
+                            if ui
+                                .button(format!("📂 {name}"))
+                                .on_hover_text(path.display().to_string())
+                                .clicked()
+                            {
+                                opened = Some(path.clone());
+                            }
+                        }
+                        // Le dialogue se ferme une fois le document ouvert
+                        if let Some(path) = opened {
+                            self.open_file(path);
+                        }
+                    }
+                });
+        }
+
+        // --- Mode plein écran : seule une pastille permet de revenir à l'interface ---
+        if self.focus_mode {
+            egui::Area::new("focus_exit")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style())
+                        .rounding(12.0)
+                        .show(ui, |ui| {
+                            if let Some(shown) = self.presentation {
+                                ui.horizontal(|ui| {
+                                    let (current, total) = self.step_count(shown);
+                                    ui.label(tr_fill(
+                                        "Étape {} / {}",
+                                        &[&current.to_string(), &total.to_string()],
+                                    ))
+                                    .on_hover_text(tr(
+                                        "→ : étape suivante, ← : étape précédente\n\
+                                         Début / Fin : tout masquer / tout révéler",
+                                    ));
+                                    if ui
+                                        .small_button(tr("Quitter la présentation"))
+                                        .on_hover_text(tr("Échap"))
+                                        .clicked()
+                                    {
+                                        self.run_command(ctx, "view.presentation");
+                                    }
+                                });
+                            } else if ui
+                                .small_button(tr("⛶ Afficher l'interface"))
+                                .on_hover_text(self.shortcut_text(ctx, "view.focus"))
+                                .clicked()
+                            {
+                                self.run_command(ctx, "view.focus");
+                            }
+                        });
+                });
+        }
+
+        // --- Confirmations ---
+        if self.confirm_clear_open {
+            egui::Window::new(tr("Effacer tout ?"))
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let count = self.document.len().to_string();
+                    ui.label(tr_fill(
+                        "Les {} traits du dessin seront supprimés (Ctrl+Z pour annuler).",
+                        &[&count],
+                    ));
+                    let mut dont_ask = !self.prefs.confirm_clear;
+                    ui.checkbox(&mut dont_ask, tr("Ne plus demander"));
+                    self.prefs.confirm_clear = !dont_ask;
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("🗑 Effacer tout")).clicked() {
+                            self.clear_all();
+                            self.confirm_clear_open = false;
+                        }
+                        if ui.button(tr("Annuler")).clicked() {
+                            self.confirm_clear_open = false;
+                        }
+                    });
+                });
+        }
+        if self.confirm_close_open {
+            egui::Window::new(tr("Quitter sans enregistrer ?"))
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(tr("Le dessin en cours sera perdu."));
+                    ui.horizontal(|ui| {
+                        // Si l'enregistrement échoue, la fenêtre reste ouverte
+                        if ui.button(tr("💾 Enregistrer et quitter")).clicked() && self.save_file()
+                        {
+                            self.close_allowed = true;
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                        if ui.button(tr("Quitter")).clicked() {
+                            self.close_allowed = true;
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                        if ui.button(tr("Annuler")).clicked() {
+                            self.confirm_close_open = false;
+                        }
+                    });
+                });
+        }
+
+        // --- Propriétés du document ---
+        if self.properties_edit.is_some() {
+            self.properties_window(ctx);
+        }
+
+        // --- Notifications ---
+        if let Some(retry) = self.toasts.ui(ctx, self.canvas_rect) {
+            retry(self, ctx);
+        }
+
+        // --- Console du journal ---
+        if self.show_log {
+            self.log_window(ctx);
+        }
+
+        // --- Profileur : coût de la dernière image ---
+        if self.show_profiler {
+            // Dans le coin opposé au panneau de réglages
+            let (corner, offset) = if self.prefs.panel_right {
+                (egui::Align2::LEFT_TOP, egui::vec2(8.0, 8.0))
+            } else {
+                (egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            };
+            egui::Area::new("profiler")
+                .anchor(corner, offset)
+                .interactable(false)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let frame_time = frame.info().cpu_usage.unwrap_or_default();
+                        let frame_ms = format!("{:.2}", frame_time * 1000.0);
+                        let bake_ms = format!("{:.2}", self.stats.bake_time.as_secs_f32() * 1000.0);
+                        let triangles = self.stats.baked_triangles.to_string();
+                        let shapes = self.stats.shapes.to_string();
+                        let hit_ms =
+                            format!("{:.3}", self.stats.hit_test_time.as_secs_f32() * 1000.0);
+                        let lines = self.document.len().to_string();
+                        ui.monospace(tr_fill("Image         : {} ms", &[&frame_ms]));
+                        ui.monospace(tr_fill(
+                            "Rastérisation : {} ms ({} triangles)",
+                            &[&bake_ms, &triangles],
+                        ));
+                        ui.monospace(tr_fill("Formes        : {}", &[&shapes]));
+                        ui.monospace(tr_fill("Contact       : {} ms", &[&hit_ms]));
+                        ui.monospace(tr_fill("Traits        : {}", &[&lines]));
+                    });
+                });
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Chargement d'outils depuis des bibliothèques dynamiques (fonctionnalité `plugins`).
+// Une extension est une bibliothèque `cdylib`, compilée avec la même version de Rust et de
+// `rust_paint`, qui exporte la fonction `rpaint_register_tools` : voir
+// `examples/spray_plugin.rs`.
+
+use std::path::Path;
+
+use crate::tool::Tool;
+
+/// Nom de la fonction exportée par les extensions.
+pub const REGISTER_SYMBOL: &str = "rpaint_register_tools";
+
+/// Signature de la fonction exportée par les extensions : elle ajoute ses outils à la liste.
+pub type RegisterTools = fn(&mut Vec<Box<dyn Tool>>);
+
+/// Charge les extensions du dossier `dir` et ajoute leurs outils à `tools`.
+/// Retourne une erreur par bibliothèque qui n'a pas pu être chargée.
+pub fn load_plugins(dir: &Path, tools: &mut Vec<Box<dyn Tool>>) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+
+    let mut errors = Vec::new();
+    for path in paths {
+        // L'extension est du code natif placé là par l'utilisateur ; sa fonction doit avoir
+        // exactement la signature `RegisterTools`
+        let result = unsafe {
+            libloading::Library::new(&path).and_then(|library| {
+                let register = *library.get::<RegisterTools>(REGISTER_SYMBOL.as_bytes())?;
+                register(tools);
+                // Le code des outils reste dans la bibliothèque : elle n'est jamais déchargée
+                std::mem::forget(library);
+                Ok(())
+            })
+        };
+        match result {
+            Ok(()) => tracing::info!(path = %path.display(), "extension chargée"),
+            Err(error) => errors.push(format!("{} : {error}", path.display())),
+        }
+    }
+    errors
+}
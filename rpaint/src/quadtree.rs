@@ -0,0 +1,199 @@
+// Index spatial des objets du dessin : arbre quaternaire des rectangles englobants, tenu à
+// jour par le document à chaque action. Le test de contact, la sélection et le rendu des
+// zones visibles n'examinent ainsi que les objets proches, au lieu de tout le dessin.
+//
+// Les objets sont désignés par leur indice dans le document : une insertion ou une
+// suppression au milieu du dessin décale les indices suivants (`shift`).
+
+use epaint::{Rect, Vec2};
+
+// Nombre d'objets d'une feuille au-delà duquel elle est découpée en quatre
+const NODE_CAPACITY: usize = 8;
+
+// Profondeur au-delà de laquelle les nœuds ne sont plus découpés
+const MAX_DEPTH: usize = 16;
+
+// Côté minimal de la racine, en unités du dessin
+const ROOT_SIZE: f32 = 1024.0;
+
+#[derive(Clone)]
+struct Node {
+    rect: Rect,                // Zone carrée couverte par le nœud
+    items: Vec<(usize, Rect)>, // Objets qui ne tiennent dans aucun des quarts
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl Node {
+    fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    // Quart `i` de la zone : 0 et 1 en haut, 1 et 3 à droite
+    fn quarter(rect: Rect, i: usize) -> Rect {
+        let half = rect.size() / 2.0;
+        let min = rect.min + Vec2::new(half.x * (i % 2) as f32, half.y * (i / 2) as f32);
+        Rect::from_min_size(min, half)
+    }
+
+    fn insert(&mut self, index: usize, bounds: Rect, depth: usize) {
+        if let Some(children) = &mut self.children
+            && let Some(child) = children.iter_mut().find(|c| c.rect.contains_rect(bounds))
+        {
+            child.insert(index, bounds, depth + 1);
+            return;
+        }
+        self.items.push((index, bounds));
+        if self.children.is_none() && self.items.len() > NODE_CAPACITY && depth < MAX_DEPTH {
+            self.split(depth);
+        }
+    }
+
+    // Crée les quatre quarts et y descend les objets qui y tiennent
+    fn split(&mut self, depth: usize) {
+        self.children = Some(Box::new(
+            [0, 1, 2, 3].map(|i| Node::new(Self::quarter(self.rect, i))),
+        ));
+        for (index, bounds) in std::mem::take(&mut self.items) {
+            self.insert(index, bounds, depth);
+        }
+    }
+
+    fn remove(&mut self, index: usize, bounds: Rect) -> bool {
+        if let Some(position) = self.items.iter().position(|&(i, _)| i == index) {
+            self.items.swap_remove(position);
+            return true;
+        }
+        self.children.as_mut().is_some_and(|children| {
+            children
+                .iter_mut()
+                .filter(|child| child.rect.contains_rect(bounds))
+                .any(|child| child.remove(index, bounds))
+        })
+    }
+
+    fn shift(&mut self, from: usize, delta: isize) {
+        for (index, _) in &mut self.items {
+            if *index >= from {
+                *index = index.wrapping_add_signed(delta);
+            }
+        }
+        for child in self
+            .children
+            .iter_mut()
+            .flat_map(|children| children.iter_mut())
+        {
+            child.shift(from, delta);
+        }
+    }
+
+    fn query(&self, rect: Rect, out: &mut Vec<usize>) {
+        if !self.rect.intersects(rect) {
+            return;
+        }
+        out.extend(
+            self.items
+                .iter()
+                .filter(|(_, bounds)| bounds.intersects(rect))
+                .map(|&(index, _)| index),
+        );
+        for child in self.children.iter().flat_map(|children| children.iter()) {
+            child.query(rect, out);
+        }
+    }
+}
+
+// Arbre quaternaire des objets d'un document.
+#[derive(Clone, Default)]
+pub(crate) struct QuadTree {
+    root: Option<Node>,
+    unbounded: Vec<usize>, // Objets sans emprise finie (groupe vide…), toujours candidats
+}
+
+// Emprise utilisable par l'arbre : finie et non vide
+fn indexable(bounds: Rect) -> bool {
+    bounds.is_finite() && bounds.min.x <= bounds.max.x && bounds.min.y <= bounds.max.y
+}
+
+impl QuadTree {
+    // Ajoute l'objet d'indice `index` ; les indices suivants doivent déjà avoir été décalés.
+    pub fn insert(&mut self, index: usize, bounds: Rect) {
+        if !indexable(bounds) || !self.grow_to(bounds) {
+            self.unbounded.push(index);
+            return;
+        }
+        if let Some(root) = &mut self.root {
+            root.insert(index, bounds, 0);
+        }
+    }
+
+    // Retire l'objet d'indice `index`, d'emprise `bounds` ; les indices suivants ne sont
+    // pas décalés.
+    pub fn remove(&mut self, index: usize, bounds: Rect) {
+        if let Some(position) = self.unbounded.iter().position(|&i| i == index) {
+            self.unbounded.swap_remove(position);
+        } else if let Some(root) = &mut self.root {
+            root.remove(index, bounds);
+        }
+    }
+
+    // Décale de `delta` les indices à partir de `from`.
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        for index in &mut self.unbounded {
+            if *index >= from {
+                *index = index.wrapping_add_signed(delta);
+            }
+        }
+        if let Some(root) = &mut self.root {
+            root.shift(from, delta);
+        }
+    }
+
+    // Indices, croissants, des objets dont l'emprise touche peut-être `rect` :
+    // à vérifier par un test exact.
+    pub fn query(&self, rect: Rect) -> Vec<usize> {
+        let mut out = self.unbounded.clone();
+        if let Some(root) = &self.root {
+            root.query(rect, &mut out);
+        }
+        out.sort_unstable();
+        out
+    }
+
+    // Agrandit la racine jusqu'à couvrir `bounds`, en doublant son côté vers elle ;
+    // retourne `false` si la zone devient trop grande pour les flottants
+    fn grow_to(&mut self, bounds: Rect) -> bool {
+        if self.root.is_none() {
+            let side = ROOT_SIZE.max(bounds.width()).max(bounds.height());
+            let rect = Rect::from_center_size(bounds.center(), Vec2::splat(side));
+            if !rect.is_finite() {
+                return false;
+            }
+            self.root = Some(Node::new(rect));
+        }
+        let Some(root) = &mut self.root else {
+            return false;
+        };
+        while !root.rect.contains_rect(bounds) {
+            let old = root.rect;
+            let (left, up) = (bounds.min.x < old.min.x, bounds.min.y < old.min.y);
+            let offset = Vec2::new(
+                if left { old.width() } else { 0.0 },
+                if up { old.height() } else { 0.0 },
+            );
+            let rect = Rect::from_min_size(old.min - offset, old.size() * 2.0);
+            if !rect.is_finite() {
+                return false;
+            }
+            // L'ancienne racine devient le quart de la nouvelle situé à l'opposé de `bounds`
+            let position = usize::from(left) + 2 * usize::from(up);
+            let mut children = [0, 1, 2, 3].map(|i| Node::new(Node::quarter(rect, i)));
+            children[position] = std::mem::replace(root, Node::new(rect));
+            root.children = Some(Box::new(children));
+        }
+        true
+    }
+}
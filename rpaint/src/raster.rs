@@ -0,0 +1,168 @@
+// Rastérisation logicielle des maillages produits par le tessellateur d'epaint.
+// Sert à « cuire » les traits validés dans une image hors écran, et à rendre un document
+// entier sans interface graphique (tests de rendu).
+
+use epaint::{Color32, ColorImage, Mesh, Pos2, Rect, TessellationOptions, Tessellator, Vec2};
+
+use crate::document::{Document, DrawObject};
+
+// Produit vectoriel 2D : positif si `c` est à gauche de l'arête a -> b
+fn edge(a: Pos2, b: Pos2, c: Pos2) -> f32 {
+    (b - a).x * (c - a).y - (b - a).y * (c - a).x
+}
+
+/// Façon dont un maillage se combine avec l'image existante.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Blend {
+    /// Le maillage est peint par-dessus l'image.
+    Over,
+    /// Le maillage efface l'image proportionnellement à son alpha (gomme).
+    Erase,
+}
+
+// Mélange en alpha prémultiplié (format natif de Color32)
+fn blend(dst: Color32, src: [f32; 4], mode: Blend) -> Color32 {
+    let keep = 1.0 - src[3] / 255.0;
+    let channel = |i: usize, d: u8| {
+        let paint = if mode == Blend::Over { src[i] } else { 0.0 };
+        (paint + d as f32 * keep).round().clamp(0.0, 255.0) as u8
+    };
+    Color32::from_rgba_premultiplied(
+        channel(0, dst.r()),
+        channel(1, dst.g()),
+        channel(2, dst.b()),
+        channel(3, dst.a()),
+    )
+}
+
+/// Dessine `mesh` dans `image`, dont le pixel (0, 0) correspond au point `origin`,
+/// à raison de `scale` pixels par unité du maillage.
+/// Retourne la zone modifiée, en pixels, ou `None` si rien n'a été touché.
+pub fn draw_mesh(
+    image: &mut ColorImage,
+    mesh: &Mesh,
+    origin: Pos2,
+    scale: f32,
+    mode: Blend,
+) -> Option<Rect> {
+    let [width, height] = image.size;
+    let to_pixels = |p: Pos2| Pos2::ZERO + (p - origin) * scale;
+    let mut dirty = Rect::NOTHING;
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [mut a, mut b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+        let area = edge(to_pixels(a.pos), to_pixels(b.pos), to_pixels(c.pos));
+        if area.abs() < f32::EPSILON {
+            continue;
+        }
+        if area < 0.0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let (pa, pb, pc) = (to_pixels(a.pos), to_pixels(b.pos), to_pixels(c.pos));
+        let area = area.abs();
+
+        let bounds = Rect::from_points(&[pa, pb, pc]);
+        let x_range = bounds.min.x.floor().max(0.0) as usize
+            ..(bounds.max.x.ceil().max(0.0) as usize).min(width);
+        let y_range = bounds.min.y.floor().max(0.0) as usize
+            ..(bounds.max.y.ceil().max(0.0) as usize).min(height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let (wa, wb, wc) = (
+                    edge(pb, pc, center),
+                    edge(pc, pa, center),
+                    edge(pa, pb, center),
+                );
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                // Interpolation des couleurs de sommets (coordonnées barycentriques)
+                let src = [0, 1, 2, 3].map(|i| {
+                    (a.color[i] as f32 * wa + b.color[i] as f32 * wb + c.color[i] as f32 * wc)
+                        / area
+                });
+                let pixel = &mut image.pixels[y * width + x];
+                *pixel = blend(*pixel, src, mode);
+                dirty = dirty.union(Rect::from_min_size(
+                    Pos2::new(x as f32, y as f32),
+                    Vec2::splat(1.0),
+                ));
+            }
+        }
+    }
+
+    dirty.is_positive().then_some(dirty)
+}
+
+/// Nombre maximal de pixels d'une image exportée (4 octets chacun) : un objet lointain,
+/// dans un fichier modifié à la main, ne doit pas épuiser la mémoire.
+pub const MAX_EXPORT_PIXELS: usize = 1 << 26;
+
+/// Raison pour laquelle un dessin ne peut pas être exporté en image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportError {
+    /// Aucun objet à rendre.
+    Empty,
+    /// L'image dépasserait [`MAX_EXPORT_PIXELS`].
+    TooLarge,
+}
+
+/// Zone exportée de tous les `documents`, et taille en pixels de l'image qui la couvre à
+/// un pixel par unité du dessin : le format du premier s'il en a un (les images d'une
+/// animation partagent leurs propriétés), sinon la zone couverte par les objets de tous.
+pub fn export_area(documents: &[&Document]) -> Result<(Rect, [usize; 2]), ExportError> {
+    let page = documents.first().and_then(|document| document.page());
+    let bounds = page.unwrap_or_else(|| {
+        documents
+            .iter()
+            .flat_map(|document| document.objects())
+            .map(DrawObject::bounds)
+            .fold(Rect::NOTHING, Rect::union)
+    });
+    if !bounds.is_positive() {
+        return Err(ExportError::Empty);
+    }
+    // Conversion saturante : une zone infinie donne `usize::MAX`, refusé ci-dessous
+    let size = [
+        bounds.width().ceil() as usize,
+        bounds.height().ceil() as usize,
+    ];
+    match size[0].checked_mul(size[1]) {
+        Some(pixels) if pixels <= MAX_EXPORT_PIXELS => Ok((bounds, size)),
+        _ => Err(ExportError::TooLarge),
+    }
+}
+
+/// Rend tous les objets de `document` dans une image transparente de `size` pixels,
+/// avec le même placement que [`draw_mesh`]. Les maillages en cache ne sont ni utilisés
+/// ni modifiés : ils peuvent avoir été calculés pour une autre échelle.
+pub fn render(document: &Document, size: [usize; 2], origin: Pos2, scale: f32) -> ColorImage {
+    let mut image = ColorImage::new(size, Color32::TRANSPARENT);
+    let visible_area =
+        Rect::from_min_size(origin, Vec2::new(size[0] as f32, size[1] as f32) / scale);
+    // Pas de texte : la taille de la texture des polices ne sert pas
+    let mut tessellator =
+        Tessellator::new(scale, TessellationOptions::default(), [1, 1], Vec::new());
+    // L'index du document écarte d'emblée les objets hors de la vue
+    let objects = document.objects();
+    let visible = document.objects_in(visible_area);
+    for line in visible.into_iter().flat_map(|index| objects[index].lines()) {
+        if line.points().len() >= 2 && line.rect().intersects(visible_area) {
+            let blend = if line.is_eraser() {
+                Blend::Erase
+            } else {
+                Blend::Over
+            };
+            draw_mesh(
+                &mut image,
+                &line.tessellate(&mut tessellator),
+                origin,
+                scale,
+                blend,
+            );
+        }
+    }
+    image
+}
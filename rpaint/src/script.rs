@@ -0,0 +1,295 @@
+// Scripts Rhai agissant sur le dessin.
+// Un script travaille sur une copie du document ; ses modifications ne sont appliquées
+// au vrai document qu'à la fin, en une seule action : un script = une annulation.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use epaint::{Color32, Pos2, Rect, pos2, vec2};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, INT};
+
+use crate::color::{color_to_hex, parse_hex_color};
+use crate::document::{Action, Document, DrawObject, Line};
+use crate::tool::Brush;
+
+// Nombre maximal d'opérations d'un script, pour qu'une boucle infinie ne bloque pas l'application
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Fonctions disponibles dans les scripts, pour l'aide de l'interface.
+pub const API: &str = "\
+color(r, g, b)  color(\"#RRGGBB\")  width(w)  opacity(o)
+line(x1, y1, x2, y2)  polyline([[x, y], …])  rect(x, y, w, h)
+circle(x, y, r)  ellipse(x, y, rx, ry)  group([i, …])
+count()  stroke_color(i)  recolor(i, \"#RRGGBB\")  rename(i, \"nom\")  delete(i)
+set_swatch(\"nom\", \"#RRGGBB\")
+erase_at(x, y)  clear()  print(texte)  command(\"edit.undo\")";
+
+/// Résultat d'un script exécuté sans erreur.
+pub struct Output {
+    pub printed: Vec<String>,  // Textes affichés par `print`
+    pub modified: bool,        // Vrai si le script a modifié le document
+    pub commands: Vec<String>, // Commandes de l'application demandées par `command`, dans l'ordre
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+// État partagé par les fonctions du script
+struct State {
+    document: Document,   // Copie de travail du document
+    actions: Vec<Action>, // Actions appliquées à la copie, à rejouer sur le vrai document
+    brush: Brush,
+}
+
+impl State {
+    fn apply(&mut self, action: Action) -> bool {
+        let applied = self.document.apply(action.clone());
+        if applied {
+            self.actions.push(action);
+        }
+        applied
+    }
+
+    fn add(&mut self, points: Vec<Pos2>) {
+        let brush = self.brush;
+        self.apply(Action::Push(
+            Line::new(points, brush.color, brush.width, brush.opacity).into(),
+        ));
+    }
+
+    fn object(&self, index: INT) -> ScriptResult<&DrawObject> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.document.objects().get(index))
+            .ok_or_else(|| format!("Indice d'objet invalide : {index}").into())
+    }
+
+    // Ajoute l'ellipse inscrite dans `rect`
+    fn add_ellipse(&mut self, rect: Rect) {
+        let brush = self.brush;
+        let ellipse = DrawObject::ellipse(rect, brush.color, brush.width, brush.opacity);
+        self.apply(Action::Push(ellipse));
+    }
+}
+
+// Nombre du script, entier ou décimal
+fn number(value: &Dynamic) -> ScriptResult<f32> {
+    value
+        .as_float()
+        .map(|value| value as f32)
+        .or_else(|_| value.as_int().map(|value| value as f32))
+        .map_err(|typ| format!("Nombre attendu, {typ} reçu").into())
+}
+
+fn color(text: &str) -> ScriptResult<Color32> {
+    parse_hex_color(text).ok_or_else(|| format!("Couleur invalide : {text}").into())
+}
+
+/// Exécute `source` sur `document`, avec `brush` comme pinceau de départ.
+/// En cas d'erreur, le document n'est pas modifié et le message d'erreur est retourné.
+#[tracing::instrument(name = "script", skip_all, fields(lines = source.lines().count()))]
+pub fn run(document: &mut Document, source: &str, brush: Brush) -> Result<Output, String> {
+    let state = Rc::new(RefCell::new(State {
+        document: document.fork(),
+        actions: Vec::new(),
+        brush,
+    }));
+    let output = Rc::new(RefCell::new(Vec::new()));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let printed = output.clone();
+    engine.on_print(move |text| printed.borrow_mut().push(text.to_owned()));
+
+    // Pinceau
+    let s = state.clone();
+    engine.register_fn("color", move |r: Dynamic, g: Dynamic, b: Dynamic| {
+        let channel = |value: &Dynamic| number(value).map(|value| value.clamp(0.0, 255.0) as u8);
+        s.borrow_mut().brush.color = Color32::from_rgb(channel(&r)?, channel(&g)?, channel(&b)?);
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("color", move |text: &str| {
+        s.borrow_mut().brush.color = color(text)?;
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("width", move |width: Dynamic| {
+        s.borrow_mut().brush.width = number(&width)?.max(0.1);
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("opacity", move |opacity: Dynamic| {
+        s.borrow_mut().brush.opacity = number(&opacity)?.clamp(0.0, 1.0);
+        ScriptResult::Ok(())
+    });
+
+    // Formes
+    let s = state.clone();
+    engine.register_fn(
+        "line",
+        move |x1: Dynamic, y1: Dynamic, x2: Dynamic, y2: Dynamic| {
+            let from = pos2(number(&x1)?, number(&y1)?);
+            let to = pos2(number(&x2)?, number(&y2)?);
+            s.borrow_mut().add(vec![from, to]);
+            ScriptResult::Ok(())
+        },
+    );
+    let s = state.clone();
+    engine.register_fn("polyline", move |points: Array| {
+        let points = points
+            .iter()
+            .map(|point| match point.clone().into_array() {
+                Ok(xy) if xy.len() == 2 => Ok(pos2(number(&xy[0])?, number(&xy[1])?)),
+                _ => Err(format!("Point [x, y] attendu : {point}").into()),
+            })
+            .collect::<ScriptResult<Vec<Pos2>>>()?;
+        s.borrow_mut().add(points);
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("circle", move |x: Dynamic, y: Dynamic, r: Dynamic| {
+        let (center, radius) = (pos2(number(&x)?, number(&y)?), number(&r)?);
+        s.borrow_mut()
+            .add_ellipse(Rect::from_center_size(center, vec2(2.0, 2.0) * radius));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn(
+        "ellipse",
+        move |x: Dynamic, y: Dynamic, rx: Dynamic, ry: Dynamic| {
+            let center = pos2(number(&x)?, number(&y)?);
+            let size = vec2(number(&rx)?, number(&ry)?) * 2.0;
+            s.borrow_mut()
+                .add_ellipse(Rect::from_center_size(center, size));
+            ScriptResult::Ok(())
+        },
+    );
+    let s = state.clone();
+    engine.register_fn(
+        "rect",
+        move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic| {
+            let min = pos2(number(&x)?, number(&y)?);
+            let rect = Rect::from_two_pos(min, min + vec2(number(&w)?, number(&h)?));
+            let mut state = s.borrow_mut();
+            let brush = state.brush;
+            let object = DrawObject::rect(rect, brush.color, brush.width, brush.opacity);
+            state.apply(Action::Push(object));
+            ScriptResult::Ok(())
+        },
+    );
+
+    // Objets existants
+    let s = state.clone();
+    engine.register_fn("count", move || s.borrow().document.len() as INT);
+    let s = state.clone();
+    engine.register_fn("stroke_color", move |index: INT| {
+        let state = s.borrow();
+        let color = state.object(index)?.color().ok_or_else(|| {
+            format!("Les traits du groupe {index} n'ont pas tous la même couleur")
+        })?;
+        ScriptResult::Ok(color_to_hex(color))
+    });
+    let s = state.clone();
+    engine.register_fn("recolor", move |index: INT, text: &str| {
+        let mut state = s.borrow_mut();
+        let recolored = state.object(index)?.recolored(color(text)?);
+        let index = index as usize;
+        state.apply(Action::Batch(vec![
+            Action::Remove(vec![index]),
+            Action::Insert(vec![(index, recolored)]),
+        ]));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("set_swatch", move |name: &str, text: &str| {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Nom de couleur vide".into());
+        }
+        let color = color(text)?;
+        s.borrow_mut()
+            .apply(Action::SetSwatch(name.to_owned(), Some(color)));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("rename", move |index: INT, name: &str| {
+        let mut state = s.borrow_mut();
+        state.object(index)?;
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        state.apply(Action::Rename(index as usize, name.map(str::to_owned)));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("delete", move |index: INT| {
+        let mut state = s.borrow_mut();
+        state.object(index)?;
+        state.apply(Action::Remove(vec![index as usize]));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("group", move |indices: Array| {
+        let mut state = s.borrow_mut();
+        let mut indices = indices
+            .iter()
+            .map(|index| {
+                let index = index
+                    .as_int()
+                    .map_err(|typ| format!("Indice attendu, {typ} reçu"))?;
+                state.object(index)?;
+                ScriptResult::Ok(index as usize)
+            })
+            .collect::<ScriptResult<Vec<usize>>>()?;
+        indices.sort_unstable();
+        indices.dedup();
+        let Some(&first) = indices.first() else {
+            return ScriptResult::Ok(());
+        };
+        // Le groupe prend la place de son objet le plus bas
+        let objects = indices
+            .iter()
+            .map(|&index| state.document.objects()[index].clone())
+            .collect();
+        state.apply(Action::Batch(vec![
+            Action::Remove(indices),
+            Action::Insert(vec![(first, DrawObject::group(objects))]),
+        ]));
+        ScriptResult::Ok(())
+    });
+    let s = state.clone();
+    engine.register_fn("erase_at", move |x: Dynamic, y: Dynamic| {
+        let pos = pos2(number(&x)?, number(&y)?);
+        let mut state = s.borrow_mut();
+        // Les scripts désignent des positions exactes : pas de marge autour des traits
+        let hits = state.document.objects_at(pos, 0.0);
+        let count = hits.len() as INT;
+        state.apply(Action::Remove(hits));
+        ScriptResult::Ok(count)
+    });
+    let s = state.clone();
+    engine.register_fn("clear", move || {
+        let mut state = s.borrow_mut();
+        let all = (0..state.document.len()).collect();
+        state.apply(Action::Remove(all));
+    });
+
+    // Commandes de l'application : exécutées par elle après le script
+    let commands = Rc::new(RefCell::new(Vec::new()));
+    let requested = commands.clone();
+    engine.register_fn("command", move |id: &str| {
+        requested.borrow_mut().push(id.to_owned());
+    });
+
+    if let Err(error) = engine.run(source) {
+        tracing::info!(%error, "échec du script");
+        return Err(error.to_string());
+    }
+
+    let actions = std::mem::take(&mut state.borrow_mut().actions);
+    let modified = !actions.is_empty() && document.apply(Action::Batch(actions));
+    tracing::info!(modified, "script exécuté");
+    Ok(Output {
+        printed: output.take(),
+        modified,
+        commands: commands.take(),
+    })
+}
@@ -0,0 +1,122 @@
+// Notifications affichées quelques secondes en bas à droite du canevas : confirmations et
+// erreurs (fichiers illisibles, extensions ignorées), avec au besoin un bouton pour réessayer.
+
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::PaintApp;
+use crate::i18n::tr;
+
+// Durée d'affichage, en secondes ; une notification survolée reste affichée
+const INFO_DURATION: f64 = 4.0;
+const ERROR_DURATION: f64 = 10.0;
+
+// Nouvelle tentative de l'opération qui a échoué
+pub type Retry = Box<dyn FnOnce(&mut PaintApp, &egui::Context)>;
+
+struct Toast {
+    id: u64, // Identifiant de la zone egui
+    text: String,
+    error: bool,
+    retry: Option<Retry>,
+    expires: Option<f64>, // Instant (temps d'egui) de disparition, fixé au premier affichage
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    list: Vec<Toast>, // La plus ancienne en premier
+    next_id: u64,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        tracing::info!("{text}");
+        self.push(text, false, None);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        tracing::warn!("{text}");
+        self.push(text, true, None);
+    }
+
+    // Erreur proposant de relancer l'opération
+    pub fn error_with_retry(&mut self, text: impl Into<String>, retry: Retry) {
+        let text = text.into();
+        tracing::warn!("{text}");
+        self.push(text, true, Some(retry));
+    }
+
+    fn push(&mut self, text: String, error: bool, retry: Option<Retry>) {
+        // Une même erreur répétée ne s'affiche qu'une fois, avec la dernière tentative
+        self.list.retain(|toast| toast.text != text);
+        self.list.push(Toast {
+            id: self.next_id,
+            text,
+            error,
+            retry,
+            expires: None,
+        });
+        self.next_id += 1;
+    }
+
+    // Affiche les notifications empilées depuis le coin inférieur droit de `rect`,
+    // et retourne la nouvelle tentative demandée
+    pub fn ui(&mut self, ctx: &egui::Context, rect: egui::Rect) -> Option<Retry> {
+        let now = ctx.input(|input| input.time);
+        let mut retry = None;
+        let mut corner = rect.right_bottom() - egui::vec2(8.0, 8.0);
+        let mut open = vec![true; self.list.len()];
+        for (toast, open) in self.list.iter_mut().zip(&mut open).rev() {
+            let duration = if toast.error {
+                ERROR_DURATION
+            } else {
+                INFO_DURATION
+            };
+            let area = egui::Area::new(egui::Id::new(("toast", toast.id)))
+                .pivot(egui::Align2::RIGHT_BOTTOM)
+                .fixed_pos(corner)
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(320.0);
+                        ui.horizontal(|ui| {
+                            if toast.error {
+                                ui.colored_label(ui.visuals().error_fg_color, "⚠");
+                            }
+                            ui.label(&toast.text);
+                            if toast.retry.is_some() && ui.button(tr("Réessayer")).clicked() {
+                                retry = toast.retry.take();
+                                *open = false;
+                            }
+                            if ui.small_button("✖").on_hover_text(tr("Fermer")).clicked() {
+                                *open = false;
+                            }
+                        });
+                    });
+                    ui.ui_contains_pointer()
+                });
+            corner.y -= area.response.rect.height() + 6.0;
+            let hovered = area.inner;
+            if hovered || toast.expires.is_none() {
+                toast.expires = Some(now + duration);
+            } else if toast.expires.is_some_and(|expires| now >= expires) {
+                *open = false;
+            }
+        }
+        let mut open = open.into_iter();
+        self.list.retain(|_| open.next().unwrap_or(true));
+        // Réveil pour faire disparaître la prochaine notification à temps
+        let next = self
+            .list
+            .iter()
+            .filter_map(|toast| toast.expires)
+            .reduce(f64::min);
+        if let Some(next) = next {
+            ctx.request_repaint_after(Duration::from_secs_f64((next - now).max(0.0)));
+        }
+        retry
+    }
+}
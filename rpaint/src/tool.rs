@@ -0,0 +1,227 @@
+// Outils de dessin : ils reçoivent les événements du pointeur, en coordonnées du dessin,
+// et produisent des actions sur le document ainsi qu'un aperçu du geste en cours.
+// Les outils intégrés et ceux des extensions passent tous par le trait `Tool`.
+
+use epaint::{Color32, Pos2, Rect};
+
+use crate::document::{Action, Document, DrawObject, Line};
+
+/// Pinceau actif : couleur, épaisseur et opacité des traits produits.
+#[derive(Clone, Copy)]
+pub struct Brush {
+    pub color: Color32,
+    pub width: f32,
+    pub opacity: f32,
+}
+
+/// Événement du pointeur, en coordonnées du dessin.
+#[derive(Clone, Copy, Debug)]
+pub enum PointerEvent {
+    /// Début du geste : bouton enfoncé, ou crayon posé au clavier.
+    Press(Pos2),
+    /// Déplacement pendant le geste.
+    Move(Pos2),
+    /// Fin du geste.
+    Release,
+}
+
+/// Outil de dessin.
+pub trait Tool {
+    /// Nom de l'outil, qui l'identifie aussi dans les préréglages.
+    fn name(&self) -> &'static str;
+
+    /// Icône des boutons d'outil.
+    fn icon(&self) -> &'static str;
+
+    /// Traite un événement du pointeur et retourne les actions à appliquer au document.
+    fn pointer_event(
+        &mut self,
+        event: PointerEvent,
+        brush: Brush,
+        document: &Document,
+    ) -> Vec<Action>;
+
+    /// Aperçu du geste en cours : traits pas encore ajoutés au document.
+    fn preview(&self, brush: Brush) -> Vec<Line>;
+
+    /// Abandonne le geste en cours.
+    fn cancel(&mut self);
+}
+
+/// Trait à main levée ; en gomme, ses traits effacent ceux du dessous au lieu de peindre.
+#[derive(Default)]
+pub struct Freehand {
+    points: Vec<Pos2>, // Points du geste en cours
+    erase: bool,
+}
+
+impl Freehand {
+    pub fn eraser() -> Self {
+        Self {
+            points: Vec::new(),
+            erase: true,
+        }
+    }
+
+    fn line(&self, points: Vec<Pos2>, brush: Brush) -> Line {
+        Line::new(points, brush.color, brush.width, brush.opacity).with_erase(self.erase)
+    }
+}
+
+impl Tool for Freehand {
+    fn name(&self) -> &'static str {
+        if self.erase { "Gomme" } else { "Main levée" }
+    }
+
+    fn icon(&self) -> &'static str {
+        if self.erase { "🧽" } else { "✏" }
+    }
+
+    fn pointer_event(&mut self, event: PointerEvent, brush: Brush, _: &Document) -> Vec<Action> {
+        match event {
+            PointerEvent::Press(pos) => self.points = vec![pos],
+            PointerEvent::Move(pos) if !self.points.is_empty() => self.points.push(pos),
+            PointerEvent::Move(_) => {}
+            PointerEvent::Release => {
+                let points = std::mem::take(&mut self.points);
+                if points.len() >= 2 {
+                    return vec![Action::Push(self.line(points, brush).into())];
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn preview(&self, brush: Brush) -> Vec<Line> {
+        if self.points.len() < 2 {
+            return Vec::new();
+        }
+        vec![self.line(self.points.clone(), brush)]
+    }
+
+    fn cancel(&mut self) {
+        self.points.clear();
+    }
+}
+
+/// Segment de droite entre le début et la fin du geste.
+#[derive(Default)]
+pub struct StraightLine {
+    ends: Option<(Pos2, Pos2)>, // Extrémités du segment en cours
+}
+
+impl Tool for StraightLine {
+    fn name(&self) -> &'static str {
+        "Ligne"
+    }
+
+    fn icon(&self) -> &'static str {
+        "📏"
+    }
+
+    fn pointer_event(&mut self, event: PointerEvent, brush: Brush, _: &Document) -> Vec<Action> {
+        match event {
+            PointerEvent::Press(pos) => self.ends = Some((pos, pos)),
+            PointerEvent::Move(pos) => {
+                if let Some((_, end)) = &mut self.ends {
+                    *end = pos;
+                }
+            }
+            PointerEvent::Release => {
+                if let Some((start, end)) = self.ends.take()
+                    && start != end
+                {
+                    let line = Line::new(vec![start, end], brush.color, brush.width, brush.opacity);
+                    return vec![Action::Push(line.into())];
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn preview(&self, brush: Brush) -> Vec<Line> {
+        self.ends
+            .map(|(start, end)| {
+                Line::new(vec![start, end], brush.color, brush.width, brush.opacity)
+            })
+            .into_iter()
+            .collect()
+    }
+
+    fn cancel(&mut self) {
+        self.ends = None;
+    }
+}
+
+/// Rectangle ou ellipse tendu entre le début et la fin du geste.
+pub struct Outline {
+    corners: Option<(Pos2, Pos2)>, // Coins opposés de la forme en cours
+    ellipse: bool,
+}
+
+impl Outline {
+    pub fn rectangle() -> Self {
+        Self {
+            corners: None,
+            ellipse: false,
+        }
+    }
+
+    pub fn ellipse() -> Self {
+        Self {
+            corners: None,
+            ellipse: true,
+        }
+    }
+
+    fn object(&self, start: Pos2, end: Pos2, brush: Brush) -> DrawObject {
+        let rect = Rect::from_two_pos(start, end);
+        if self.ellipse {
+            DrawObject::ellipse(rect, brush.color, brush.width, brush.opacity)
+        } else {
+            DrawObject::rect(rect, brush.color, brush.width, brush.opacity)
+        }
+    }
+}
+
+impl Tool for Outline {
+    fn name(&self) -> &'static str {
+        if self.ellipse { "Ellipse" } else { "Rectangle" }
+    }
+
+    fn icon(&self) -> &'static str {
+        if self.ellipse { "⭕" } else { "⬜" }
+    }
+
+    fn pointer_event(&mut self, event: PointerEvent, brush: Brush, _: &Document) -> Vec<Action> {
+        match event {
+            PointerEvent::Press(pos) => self.corners = Some((pos, pos)),
+            PointerEvent::Move(pos) => {
+                if let Some((_, end)) = &mut self.corners {
+                    *end = pos;
+                }
+            }
+            PointerEvent::Release => {
+                if let Some((start, end)) = self.corners.take()
+                    && start.x != end.x
+                    && start.y != end.y
+                {
+                    return vec![Action::Push(self.object(start, end, brush))];
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn preview(&self, brush: Brush) -> Vec<Line> {
+        self.corners
+            .map(|(start, end)| self.object(start, end, brush))
+            .map_or_else(Vec::new, |object| {
+                object.lines().into_iter().cloned().collect()
+            })
+    }
+
+    fn cancel(&mut self) {
+        self.corners = None;
+    }
+}
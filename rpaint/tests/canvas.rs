@@ -0,0 +1,74 @@
+// Tests du widget de dessin sans fenêtre : les images d'egui sont produites avec un
+// contexte seul, et les gestes simulés par des événements du pointeur.
+
+use egui::{Context, Event, PointerButton, Pos2, RawInput, Rect, pos2, vec2};
+use rust_paint::canvas::PaintCanvas;
+
+// Fait passer une image d'egui avec `events`, le widget occupant toute la fenêtre ;
+// retourne `true` si le widget signale une modification du document
+fn frame(ctx: &Context, canvas: &mut PaintCanvas, events: Vec<Event>) -> bool {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(200.0, 150.0))),
+        events,
+        ..RawInput::default()
+    };
+    let mut changed = false;
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| changed = canvas.show(ui).changed());
+    });
+    changed
+}
+
+fn button(pos: Pos2, pressed: bool) -> Event {
+    Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    }
+}
+
+// Trace un geste de `points` dans le widget, une image par événement ;
+// retourne `true` si une des images a signalé une modification
+fn drag(ctx: &Context, canvas: &mut PaintCanvas, points: &[Pos2]) -> bool {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut changed = frame(ctx, canvas, vec![Event::PointerMoved(first)]);
+    changed |= frame(ctx, canvas, vec![button(first, true)]);
+    for &point in &points[1..] {
+        changed |= frame(ctx, canvas, vec![Event::PointerMoved(point)]);
+    }
+    changed |= frame(ctx, canvas, vec![button(last, false)]);
+    changed |= frame(ctx, canvas, Vec::new());
+    changed
+}
+
+#[test]
+fn drag_adds_a_stroke_and_reports_the_change() {
+    let ctx = Context::default();
+    let mut canvas = PaintCanvas::new().with_toolbar(false);
+    let points = [pos2(20.0, 20.0), pos2(60.0, 40.0), pos2(100.0, 90.0)];
+    assert!(drag(&ctx, &mut canvas, &points));
+
+    assert_eq!(canvas.document().len(), 1);
+    let lines = canvas.document().objects()[0].lines();
+    let stroke = lines[0].points();
+    // Coordonnées relatives au coin de la zone, placée dans la marge du panneau
+    assert!(stroke.len() >= 2);
+    assert!(stroke.iter().all(|point| point.x < 100.0 && point.y < 90.0));
+    assert!(!frame(&ctx, &mut canvas, Vec::new()));
+}
+
+#[test]
+fn selected_tool_and_undo() {
+    let ctx = Context::default();
+    let mut canvas = PaintCanvas::new().with_toolbar(false);
+    assert!(canvas.select_tool(3));
+    assert!(!canvas.select_tool(99));
+    drag(&ctx, &mut canvas, &[pos2(30.0, 30.0), pos2(120.0, 100.0)]);
+
+    assert_eq!(canvas.document().objects()[0].kind_name(), "Rectangle");
+    assert!(canvas.undo());
+    assert!(canvas.document().is_empty());
+    assert!(canvas.redo());
+    assert_eq!(canvas.document().len(), 1);
+}
@@ -0,0 +1,188 @@
+// Primitives géométriques, test de contact et sélection au cadre et au lasso : un trait qui
+// traverse la zone sans y avoir de point doit être retenu, l'épaisseur et le zoom comptent.
+
+use epaint::{Color32, Pos2, Rect, pos2};
+use rust_paint::document::{Document, Line};
+use rust_paint::geometry::{
+    InputDevice, distance_between_segments, distance_segment_to_polygon, distance_segment_to_rect,
+    distance_to_segment, hit_tolerance, polygon_contains, segment_intersects_circle,
+    segment_intersects_polygon, segment_intersects_rect, segments_intersect,
+};
+
+fn square(min: f32, max: f32) -> Rect {
+    Rect::from_min_max(pos2(min, min), pos2(max, max))
+}
+
+// Triangle et forme en « U », concave, pour le lasso
+fn triangle() -> Vec<Pos2> {
+    vec![pos2(0.0, 0.0), pos2(100.0, 0.0), pos2(50.0, 100.0)]
+}
+
+fn u_shape() -> Vec<Pos2> {
+    [
+        (0, 0),
+        (30, 0),
+        (30, 70),
+        (70, 70),
+        (70, 0),
+        (100, 0),
+        (100, 100),
+        (0, 100),
+    ]
+    .map(|(x, y)| pos2(x as f32, y as f32))
+    .to_vec()
+}
+
+#[test]
+fn segment_distance_and_crossings() {
+    let (a, b) = (pos2(0.0, 0.0), pos2(10.0, 10.0));
+    assert_eq!(distance_to_segment(pos2(10.0, 0.0), a, b), 50f32.sqrt());
+    assert_eq!(distance_to_segment(pos2(13.0, 14.0), a, b), 5.0);
+    // Segment réduit à un point
+    assert_eq!(distance_to_segment(pos2(3.0, 4.0), a, a), 5.0);
+
+    let crossing = (pos2(0.0, 10.0), pos2(10.0, 0.0));
+    let parallel = (pos2(0.0, 1.0), pos2(9.0, 10.0));
+    assert!(segments_intersect(a, b, crossing.0, crossing.1));
+    assert!(!segments_intersect(a, b, parallel.0, parallel.1));
+    // Extrémité posée sur l'autre segment, segments alignés qui se chevauchent ou non
+    let middle = pos2(5.0, 5.0);
+    assert!(segments_intersect(a, b, middle, crossing.1));
+    assert!(segments_intersect(a, b, middle, pos2(20.0, 20.0)));
+    assert!(!segments_intersect(
+        a,
+        b,
+        pos2(11.0, 11.0),
+        pos2(20.0, 20.0)
+    ));
+
+    assert_eq!(distance_between_segments(a, b, crossing.0, crossing.1), 0.0);
+    let distance = distance_between_segments(a, b, parallel.0, parallel.1);
+    assert!((distance - 0.5f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn segment_against_rect_and_circle() {
+    let rect = square(10.0, 20.0);
+    let across = (pos2(0.0, 15.0), pos2(30.0, 15.0));
+    let diagonal = (pos2(0.0, 0.0), pos2(30.0, 30.0));
+    // Passe à côté du coin, bien que les rectangles englobants se chevauchent
+    let beside = (pos2(0.0, 15.0), pos2(15.0, 30.0));
+    let above = (pos2(0.0, 23.0), pos2(30.0, 23.0));
+    // Traverse le rectangle de part en part, sans extrémité à l'intérieur
+    assert!(segment_intersects_rect(across.0, across.1, rect));
+    assert!(segment_intersects_rect(diagonal.0, diagonal.1, rect));
+    assert!(segment_intersects_rect(
+        pos2(12.0, 12.0),
+        pos2(13.0, 13.0),
+        rect
+    ));
+    assert!(!segment_intersects_rect(beside.0, beside.1, rect));
+    assert!(!segment_intersects_rect(
+        diagonal.0,
+        diagonal.1,
+        Rect::NOTHING
+    ));
+    assert_eq!(distance_segment_to_rect(across.0, across.1, rect), 0.0);
+    assert_eq!(distance_segment_to_rect(above.0, above.1, rect), 3.0);
+
+    let (a, b) = (pos2(0.0, 0.0), pos2(10.0, 0.0));
+    assert!(segment_intersects_circle(a, b, pos2(5.0, 2.0), 2.0));
+    assert!(!segment_intersects_circle(a, b, pos2(5.0, 3.0), 2.0));
+}
+
+#[test]
+fn polygon_containment() {
+    let triangle = triangle();
+    assert!(polygon_contains(&triangle, pos2(50.0, 30.0)));
+    assert!(!polygon_contains(&triangle, pos2(10.0, 80.0)));
+    assert!(!polygon_contains(&[], pos2(0.0, 0.0)));
+
+    let u = u_shape();
+    assert!(polygon_contains(&u, pos2(15.0, 50.0)));
+    assert!(polygon_contains(&u, pos2(50.0, 85.0)));
+    // Creux du U : à l'intérieur de l'enveloppe, mais pas du polygone
+    assert!(!polygon_contains(&u, pos2(50.0, 30.0)));
+
+    // Traverse le creux sans extrémité dans le polygone
+    let through = (pos2(50.0, 30.0), pos2(50.0, 200.0));
+    let hollow = (pos2(40.0, 10.0), pos2(40.0, 60.0));
+    assert!(segment_intersects_polygon(through.0, through.1, &u));
+    assert!(!segment_intersects_polygon(hollow.0, hollow.1, &u));
+    assert_eq!(distance_segment_to_polygon(hollow.0, hollow.1, &u), 10.0);
+    let empty = distance_segment_to_polygon(hollow.0, hollow.1, &[]);
+    assert_eq!(empty, f32::INFINITY);
+}
+
+fn stroke(points: &[(f32, f32)], width: f32) -> Line {
+    let points = points.iter().map(|&(x, y)| pos2(x, y)).collect();
+    Line::new(points, Color32::BLACK, width, 1.0)
+}
+
+#[test]
+fn marquee_keeps_strokes_crossing_the_rect() {
+    let mut document = Document::new();
+    document.add_stroke(stroke(&[(0.0, 50.0), (100.0, 50.0)], 2.0));
+    document.add_stroke(stroke(&[(0.0, 0.0), (10.0, 5.0)], 2.0));
+    // Trait épais qui passe juste au-dessus du cadre : son épaisseur le touche
+    document.add_stroke(stroke(&[(0.0, 36.0), (100.0, 36.0)], 10.0));
+    assert_eq!(document.select_rect(square(40.0, 60.0)), vec![0, 2]);
+    assert!(document.select_rect(square(200.0, 300.0)).is_empty());
+}
+
+#[test]
+fn lasso_follows_the_polygon_outline() {
+    let mut document = Document::new();
+    // Dans le creux du U, puis à travers le creux, puis dans une branche
+    document.add_stroke(stroke(&[(45.0, 20.0), (55.0, 40.0)], 2.0));
+    document.add_stroke(stroke(&[(50.0, 20.0), (50.0, 150.0)], 2.0));
+    document.add_stroke(stroke(&[(10.0, 40.0), (20.0, 60.0)], 2.0));
+    // Contre le bord intérieur du U : seule l'épaisseur le touche
+    document.add_stroke(stroke(&[(35.0, 10.0), (35.0, 60.0)], 12.0));
+    assert_eq!(document.select_lasso(&u_shape()), vec![1, 2, 3]);
+    assert!(document.select_lasso(&u_shape()[..2]).is_empty());
+}
+
+#[test]
+fn hit_test_counts_half_the_width() {
+    let line = stroke(&[(0.0, 0.0), (100.0, 0.0)], 10.0);
+    assert!(line.contains(pos2(50.0, 4.9)));
+    assert!(!line.contains(pos2(50.0, 5.1)));
+    assert!(line.intersects_circle(pos2(50.0, 8.0), 3.5));
+    assert!(!line.intersects_circle(pos2(50.0, 8.0), 2.5));
+    // Un point isolé se touche aussi
+    assert!(stroke(&[(10.0, 10.0)], 4.0).contains(pos2(11.0, 11.0)));
+}
+
+#[test]
+fn tolerance_follows_zoom_and_device() {
+    let mouse = hit_tolerance(1.0, InputDevice::Mouse);
+    assert_eq!(hit_tolerance(4.0, InputDevice::Mouse), mouse / 4.0);
+    assert!(hit_tolerance(1.0, InputDevice::Touch) > mouse);
+
+    let mut document = Document::new();
+    document.add_stroke(stroke(&[(0.0, 0.0), (100.0, 0.0)], 4.0));
+    document.add_stroke(stroke(&[(0.0, 10.0), (100.0, 10.0)], 4.0));
+    // À 3 unités du bord du premier trait : touché de loin, manqué une fois grossi
+    let pos = pos2(50.0, 5.0);
+    assert_eq!(
+        document.top_object_at(pos, hit_tolerance(1.0, InputDevice::Mouse)),
+        Some(1)
+    );
+    assert_eq!(
+        document.objects_at(pos, hit_tolerance(1.0, InputDevice::Mouse)),
+        [0, 1]
+    );
+    assert_eq!(
+        document.top_object_at(pos, hit_tolerance(8.0, InputDevice::Mouse)),
+        None
+    );
+    assert_eq!(
+        document.top_object_at(pos, hit_tolerance(8.0, InputDevice::Touch)),
+        None
+    );
+    assert_eq!(
+        document.erase_at(pos2(50.0, 8.0), hit_tolerance(2.0, InputDevice::Touch)),
+        2
+    );
+}
@@ -0,0 +1,172 @@
+// Tests de rendu : chaque document de référence est rastérisé sans interface et comparé
+// à une image enregistrée dans `tests/golden`. Après un changement de rendu voulu, relancer
+// avec `UPDATE_GOLDEN=1` pour régénérer les images, puis relire les différences.
+
+use epaint::{Color32, ColorImage, Pos2, Rect, pos2, vec2};
+use image::RgbaImage;
+use rust_paint::document::{Action, Document, DrawObject, Line, Properties};
+use rust_paint::raster;
+
+const SIZE: [usize; 2] = [160, 120];
+
+// Écart toléré par canal, pour les arrondis qui varient d'une plateforme à l'autre
+const TOLERANCE: u8 = 2;
+
+fn document(objects: Vec<DrawObject>) -> Document {
+    let mut document = Document::new();
+    for object in objects {
+        document.apply(Action::Push(object));
+    }
+    document
+}
+
+fn line(points: &[(f32, f32)], color: Color32, width: f32, opacity: f32) -> Line {
+    let points = points.iter().map(|&(x, y)| pos2(x, y)).collect();
+    Line::new(points, color, width, opacity)
+}
+
+fn strokes() -> Document {
+    let zigzag = [
+        (10.0, 20.0),
+        (40.0, 90.0),
+        (70.0, 25.0),
+        (100.0, 95.0),
+        (140.0, 30.0),
+    ];
+    document(vec![
+        line(&zigzag, Color32::from_rgb(200, 40, 40), 6.0, 1.0).into(),
+        line(
+            &[(15.0, 60.0), (150.0, 60.0)],
+            Color32::from_rgb(30, 90, 220),
+            12.0,
+            0.5,
+        )
+        .into(),
+        line(&[(80.0, 5.0), (80.0, 115.0)], Color32::BLACK, 1.0, 1.0).into(),
+    ])
+}
+
+fn to_rgba(image: &ColorImage) -> RgbaImage {
+    let bytes = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied());
+    RgbaImage::from_raw(image.size[0] as u32, image.size[1] as u32, bytes.collect()).unwrap()
+}
+
+// Compare le rendu à l'image de référence `name` ; en cas d'écart, le rendu obtenu est
+// écrit dans le dossier temporaire des tests pour pouvoir les comparer
+fn check(name: &str, document: &Document, origin: Pos2, scale: f32) {
+    let actual = to_rgba(&raster::render(document, SIZE, origin, scale));
+    let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save(&path).unwrap();
+        return;
+    }
+    let expected = image::open(&path)
+        .unwrap_or_else(|error| panic!("{path} : {error} (UPDATE_GOLDEN=1 pour la créer)"))
+        .to_rgba8();
+    let different = expected.dimensions() != actual.dimensions()
+        || expected
+            .pixels()
+            .zip(actual.pixels())
+            .any(|(a, b)| a.0.iter().zip(b.0).any(|(&a, b)| a.abs_diff(b) > TOLERANCE));
+    if different {
+        let output = format!("{}/{name}.png", env!("CARGO_TARGET_TMPDIR"));
+        actual.save(&output).unwrap();
+        panic!("le rendu de {name} diffère de {path} ; rendu obtenu : {output}");
+    }
+}
+
+#[test]
+fn strokes_with_width_and_opacity() {
+    check("strokes", &strokes(), Pos2::ZERO, 1.0);
+}
+
+#[test]
+fn eraser_clears_previous_strokes() {
+    let mut document = strokes();
+    let eraser = line(&[(20.0, 100.0), (140.0, 15.0)], Color32::WHITE, 14.0, 1.0);
+    document.apply(Action::Push(eraser.with_erase(true).into()));
+    check("eraser", &document, Pos2::ZERO, 1.0);
+}
+
+#[test]
+fn shapes_and_groups() {
+    let dots = (0..5)
+        .map(|i| {
+            let x = 110.0 + i as f32 * 8.0;
+            line(
+                &[(x, 80.0), (x + 3.0, 110.0)],
+                Color32::DARK_GREEN,
+                3.0,
+                1.0,
+            )
+            .into()
+        })
+        .collect();
+    let document = document(vec![
+        DrawObject::rect(
+            Rect::from_min_max(pos2(10.0, 10.0), pos2(90.0, 60.0)),
+            Color32::from_rgb(240, 160, 0),
+            4.0,
+            1.0,
+        ),
+        DrawObject::ellipse(
+            Rect::from_min_max(pos2(40.0, 40.0), pos2(150.0, 110.0)),
+            Color32::from_rgb(120, 0, 160),
+            3.0,
+            0.75,
+        ),
+        DrawObject::group(dots),
+    ]);
+    check("shapes", &document, Pos2::ZERO, 1.0);
+}
+
+#[test]
+fn zoomed_and_scrolled_view() {
+    // Vue grossie deux fois sur le quart supérieur gauche, décalée : le reste est hors champ
+    check("zoomed", &strokes(), pos2(10.0, 15.0), 2.0);
+}
+
+#[test]
+fn export_size_is_bounded() {
+    let stroke = |points: &[(f32, f32)]| DrawObject::from(line(points, Color32::RED, 2.0, 1.0));
+    let drawing = document(vec![stroke(&[(0.0, 0.0), (99.0, 49.0)])]);
+    let (area, size) = raster::export_area(&[&drawing]).unwrap();
+    assert!(area.contains(pos2(99.0, 49.0)));
+    assert_eq!(
+        size,
+        [area.width().ceil() as usize, area.height().ceil() as usize]
+    );
+
+    // Un seul point très loin : refusé avant d'allouer l'image
+    let far = document(vec![stroke(&[(0.0, 0.0), (1e9, 1e9)])]);
+    assert_eq!(
+        raster::export_area(&[&drawing, &far]),
+        Err(raster::ExportError::TooLarge)
+    );
+    assert_eq!(
+        raster::export_area(&[&Document::new()]),
+        Err(raster::ExportError::Empty)
+    );
+}
+
+#[test]
+fn page_size_sets_the_export_area() {
+    let stroke = DrawObject::from(line(&[(0.0, 0.0), (300.0, 10.0)], Color32::RED, 2.0, 1.0));
+    let mut drawing = document(vec![stroke]);
+    drawing.set_properties(Properties {
+        size: Some(vec2(200.0, 100.0)),
+        ..Properties::default()
+    });
+
+    // Le format l'emporte sur les objets, qu'il coupe ou qu'il entoure de vide
+    let page = Rect::from_min_size(Pos2::ZERO, vec2(200.0, 100.0));
+    assert_eq!(raster::export_area(&[&drawing]), Ok((page, [200, 100])));
+    assert!(drawing.export_svg().contains("viewBox=\"0 0 200 100\""));
+
+    let json = serde_json::to_string(&drawing).unwrap();
+    let read: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.page(), Some(page));
+}
@@ -0,0 +1,130 @@
+// Rejeu de journaux d'actions enregistrés dans `tests/replays` : un document de départ, puis
+// des actions appliquées, annulées et rétablies. Chaque état traversé est haché ; l'empreinte
+// de la suite doit être la même d'une exécution à l'autre et d'une plateforme à l'autre
+// (`.hash` à côté du journal). Après un changement voulu du modèle, relancer avec
+// `UPDATE_GOLDEN=1` pour régénérer les empreintes, puis relire les différences.
+
+use rust_paint::document::{Action, Document};
+use serde::Deserialize;
+use serde_json::Value;
+
+// Étape d'un journal
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Step {
+    Apply(Box<Action>),
+    Undo,
+    Redo,
+}
+
+#[derive(Deserialize)]
+struct Log {
+    document: Document,
+    steps: Vec<Step>,
+}
+
+fn read(name: &str) -> Log {
+    let path = format!("{}/tests/replays/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{path} : {error}"));
+    serde_json::from_str(&text).unwrap_or_else(|error| panic!("{path} : {error}"))
+}
+
+// Retire les dates, attribuées à partir de l'horloge au moment du rejeu
+fn without_dates(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !matches!(key.as_str(), "created_at" | "modified_at"));
+            map.values_mut().for_each(without_dates);
+        }
+        Value::Array(values) => values.iter_mut().for_each(without_dates),
+        _ => {}
+    }
+}
+
+// FNV-1a sur 64 bits : stable, contrairement au hachage de la bibliothèque standard
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+// Empreinte de l'état enregistré du document, dates exceptées
+fn hash(document: &Document) -> u64 {
+    let mut value = serde_json::to_value(document).unwrap();
+    without_dates(&mut value);
+    fnv1a(FNV_OFFSET, value.to_string().as_bytes())
+}
+
+// Rejoue le journal et retourne l'empreinte de chaque état, le départ compris, avec
+// le document final
+fn replay(log: Log) -> (Vec<u64>, Document) {
+    let mut document = log.document;
+    let mut hashes = vec![hash(&document)];
+    for step in log.steps {
+        let changed = match step {
+            Step::Apply(action) => document.apply(*action),
+            Step::Undo => document.undo(),
+            Step::Redo => document.redo(),
+        };
+        if changed {
+            hashes.push(hash(&document));
+        }
+    }
+    (hashes, document)
+}
+
+fn check(name: &str) {
+    let (hashes, mut document) = replay(read(name));
+    let (again, _) = replay(read(name));
+    assert_eq!(hashes, again, "{name} : deux rejeux différents");
+
+    // Tout annuler puis tout rétablir revient à la fin (les identifiants déjà attribués ne
+    // sont pas repris par l'annulation : les états intermédiaires peuvent différer)
+    let end = hash(&document);
+    let mut undone = 0;
+    while document.undo() {
+        undone += 1;
+    }
+    for _ in 0..undone {
+        assert!(document.redo());
+    }
+    assert_eq!(
+        hash(&document),
+        end,
+        "{name} : tout rétablir ne revient pas à la fin"
+    );
+
+    // Le document relu est le même
+    let read: Document = serde_json::from_str(&serde_json::to_string(&document).unwrap()).unwrap();
+    assert_eq!(hash(&read), end, "{name} : relecture différente");
+
+    let digest = hashes.iter().fold(FNV_OFFSET, |digest, hash| {
+        fnv1a(digest, &hash.to_le_bytes())
+    });
+    let actual = format!("{digest:016x}\n");
+    let path = format!("{}/tests/replays/{name}.hash", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("{path} : {error} (UPDATE_GOLDEN=1 pour la créer)"));
+    assert_eq!(actual, expected, "{name} : empreinte différente de {path}");
+}
+
+#[test]
+fn drawing_session() {
+    check("drawing");
+}
+
+#[test]
+fn named_colors() {
+    check("palette");
+}
+
+#[test]
+fn refused_actions() {
+    check("hostile");
+}
@@ -0,0 +1,355 @@
+// Format de sérialisation du modèle : les fichiers déjà écrits doivent rester lisibles
+// et être réécrits à l'identique.
+
+use epaint::{Color32, Rect, pos2};
+use rust_paint::document::{Action, Document, DrawObject, Line, Properties, iso_8601};
+use rust_paint::event::DocumentEvent;
+
+fn stroke(x: f32, color: Color32) -> Line {
+    Line::new(vec![pos2(x, 0.0), pos2(x + 10.0, 10.0)], color, 4.0, 0.5)
+}
+
+fn sample() -> Document {
+    let mut document = Document::with_author("lulu");
+    document.add_stroke(stroke(0.0, Color32::RED));
+    document.add_stroke(stroke(5.0, Color32::WHITE).with_erase(true));
+    let rect = Rect::from_min_max(pos2(20.0, 20.0), pos2(40.0, 30.0));
+    document.apply(Action::Push(
+        DrawObject::rect(rect, Color32::BLUE, 2.0, 1.0).with_name("cadre"),
+    ));
+    document.apply(Action::Push(DrawObject::group(vec![
+        DrawObject::ellipse(rect, Color32::GREEN, 1.0, 1.0),
+        stroke(50.0, Color32::GREEN).into(),
+    ])));
+    document
+}
+
+#[test]
+fn stroke_format_is_stable() {
+    let json = concat!(
+        r##"{"objects":["##,
+        r##"{"stroke":{"points":[{"x":0.0,"y":0.0},{"x":10.0,"y":10.0}],"##,
+        r##""color":"#FF0000","width":4.0,"opacity":0.5},"##,
+        r##""meta":{"id":1,"author":"lulu","created_at":1700000000000}},"##,
+        r##"{"stroke":{"points":[{"x":5.0,"y":0.0},{"x":15.0,"y":10.0}],"##,
+        r##""color":"#FFFFFF","width":4.0,"opacity":0.5,"erase":true},"##,
+        r##""meta":{"id":2,"author":"lulu","created_at":1700000000000,"name":"gomme"}}],"##,
+        r##""author":"lulu","next_id":3}"##,
+    );
+    let document: Document = serde_json::from_str(json).unwrap();
+    assert_eq!(document.len(), 2);
+    assert!(document.objects()[1].is_eraser());
+    assert_eq!(document.objects()[1].meta().name.as_deref(), Some("gomme"));
+    assert_eq!(serde_json::to_string(&document).unwrap(), json);
+}
+
+#[test]
+fn round_trip_keeps_every_object() {
+    let document = sample();
+    let json = serde_json::to_string(&document).unwrap();
+    let read: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.len(), document.len());
+    for (a, b) in document.objects().iter().zip(read.objects()) {
+        assert_eq!(a.meta(), b.meta());
+        assert_eq!(a.kind_name(), b.kind_name());
+        assert_eq!(a.bounds(), b.bounds());
+    }
+    assert_eq!(read.export_svg(), document.export_svg());
+    assert_eq!(read.author(), "lulu");
+}
+
+#[test]
+fn derived_data_is_rebuilt_on_read() {
+    let json = serde_json::to_string(&sample()).unwrap();
+    let read: Document = serde_json::from_str(&json).unwrap();
+    // Le rectangle englobant n'est pas enregistré : le test de contact doit marcher quand même
+    assert!(read.objects()[2].contains(pos2(40.0, 25.0)));
+    assert!(!read.objects()[2].contains(pos2(30.0, 25.0)));
+}
+
+#[test]
+fn colors_are_compact() {
+    let json = serde_json::to_string(&sample()).unwrap();
+    assert!(json.contains(r##""color":"#FF0000""##));
+    assert!(!json.contains("\"r\""));
+    let error =
+        serde_json::from_str::<Line>(r#"{"points":[],"color":"rouge","width":1.0,"opacity":1.0}"#);
+    assert!(error.is_err());
+}
+
+#[test]
+fn history_is_not_saved_but_ids_continue() {
+    let json = serde_json::to_string(&sample()).unwrap();
+    let mut read: Document = serde_json::from_str(&json).unwrap();
+    assert!(!read.can_undo());
+    read.add_stroke(stroke(0.0, Color32::RED));
+    let last = read.objects().last().unwrap().meta();
+    assert_eq!(last.id, 7);
+    assert_eq!(last.author, "lulu");
+}
+
+#[test]
+fn hostile_ids_do_not_panic() {
+    // Cas trouvé par `fuzz/` : le plus grand identifiant possible faisait déborder le suivant
+    let json = r#"{"objects":[],"next_id":18446744073709551615}"#;
+    let mut read: Document = serde_json::from_str(json).unwrap();
+    let mut object = serde_json::to_value(DrawObject::from(stroke(0.0, Color32::RED))).unwrap();
+    object["meta"]["id"] = u64::MAX.into();
+    read.apply(Action::Push(serde_json::from_value(object).unwrap()));
+    read.add_stroke(stroke(5.0, Color32::RED));
+    assert_eq!(read.len(), 2);
+}
+
+#[test]
+fn missing_and_duplicate_ids_are_replaced_on_read() {
+    // Identifiants 1, 0 et 1, avec un `next_id` trop petit : fichier modifié à la main
+    let mut objects = serde_json::to_value(sample().objects()[..3].to_vec()).unwrap();
+    for (object, id) in objects.as_array_mut().unwrap().iter_mut().zip([1, 0, 1]) {
+        object["meta"]["id"] = id.into();
+    }
+    let json = serde_json::json!({ "objects": objects, "next_id": 1 });
+    let mut read: Document = serde_json::from_value(json).unwrap();
+    read.add_stroke(stroke(0.0, Color32::RED));
+
+    let ids: Vec<u64> = read
+        .objects()
+        .iter()
+        .map(|object| object.meta().id)
+        .collect();
+    assert_eq!(ids[0], 1);
+    let mut unique = ids.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    assert_eq!(unique.len(), ids.len(), "identifiants en double : {ids:?}");
+    assert!(!ids.contains(&0));
+    assert_eq!(read.position(ids[3]), Some(3));
+}
+
+#[test]
+fn pasted_copies_get_new_ids() {
+    let mut document = sample();
+    let offset = epaint::vec2(100.0, 50.0);
+    for index in [2, 3] {
+        let copy = document.objects()[index].duplicate(offset);
+        document.apply(Action::Push(copy));
+    }
+    let (original, copy) = (&document.objects()[2], &document.objects()[4]);
+    assert_eq!(copy.bounds(), original.bounds().translate(offset));
+    assert_eq!(copy.meta().name.as_deref(), Some("cadre"));
+
+    // Objets des groupes compris
+    let mut ids = Vec::new();
+    fn collect(objects: &[DrawObject], ids: &mut Vec<u64>) {
+        for object in objects {
+            ids.push(object.meta().id);
+            if let rust_paint::document::Figure::Group(objects) = object.figure() {
+                collect(objects, ids);
+            }
+        }
+    }
+    collect(document.objects(), &mut ids);
+    let count = ids.len();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), count, "identifiants en double : {ids:?}");
+}
+
+#[test]
+fn properties_are_saved_and_exported() {
+    let mut document = sample();
+    assert_eq!(document.properties().author, "lulu");
+    let created_at = document.properties().created_at;
+    assert!(created_at > 0);
+    document.set_properties(Properties {
+        title: "Plan <R&D>".to_owned(),
+        description: "Premier jet".to_owned(),
+        background: Some(Color32::from_rgb(255, 250, 240)),
+        created_at: 1,
+        ..document.properties().clone()
+    });
+    // La date de création ne se modifie pas
+    assert_eq!(document.properties().created_at, created_at);
+    assert!(document.properties().modified_at >= created_at);
+
+    let json = serde_json::to_string(&document).unwrap();
+    assert!(json.contains(r##""background":"#FFFAF0""##));
+    let read: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.properties(), document.properties());
+
+    let svg = read.export_svg();
+    assert!(svg.contains("<title>Plan &lt;R&amp;D&gt;</title>"));
+    assert!(svg.contains("<desc>Premier jet</desc>"));
+    assert!(svg.contains("<dc:creator><cc:Agent><dc:title>lulu</dc:title>"));
+    assert!(svg.contains("fill=\"#fffaf0\""));
+}
+
+#[test]
+fn files_without_properties_stay_without() {
+    let json = r#"{"objects":[],"author":"","next_id":1}"#;
+    let read: Document = serde_json::from_str(json).unwrap();
+    assert!(read.properties().is_empty());
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    assert!(!read.export_svg().contains("<metadata>"));
+}
+
+#[test]
+fn dates_are_iso_8601() {
+    assert_eq!(iso_8601(0), "1970-01-01T00:00:00Z");
+    assert_eq!(iso_8601(1_700_000_000_000), "2023-11-14T22:13:20Z");
+    assert_eq!(iso_8601(951_782_400_000), "2000-02-29T00:00:00Z");
+}
+
+#[test]
+fn named_colors_are_saved_and_followed() {
+    let brand = Color32::from_rgb(0, 70, 160);
+    let mut document = Document::new();
+    assert!(document.set_swatch("bleu marque", brand));
+    document.add_stroke(stroke(0.0, brand).with_swatch(Some("bleu marque".to_owned())));
+    document.add_stroke(stroke(5.0, brand));
+
+    let json = serde_json::to_string(&document).unwrap();
+    assert!(json.contains(r##""palette":[{"name":"bleu marque","color":"#0046A0"}]"##));
+    let mut read: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.objects()[0].lines()[0].swatch(), Some("bleu marque"));
+
+    // Seul le trait qui fait référence à la couleur nommée la suit ; une annulation suffit
+    assert!(read.set_swatch("bleu marque", Color32::RED));
+    assert_eq!(read.objects()[0].color(), Some(Color32::RED));
+    assert_eq!(read.objects()[1].color(), Some(brand));
+    assert!(read.undo());
+    assert_eq!(read.objects()[0].color(), Some(brand));
+    assert!(!read.set_swatch("bleu marque", brand));
+}
+
+#[test]
+fn named_colors_are_shared_by_every_frame() {
+    let brand = Color32::from_rgb(0, 70, 160);
+    let mut first = Document::with_author("lulu");
+    assert!(first.set_swatch("bleu marque", brand));
+    let mut second = first.fork_empty();
+    assert_eq!(second.swatch("bleu marque"), Some(brand));
+    assert_eq!(second.author(), "lulu");
+    assert!(second.objects().is_empty());
+    second.add_stroke(stroke(0.0, brand).with_swatch(Some("bleu marque".to_owned())));
+
+    // Redéfinie depuis la première image, la couleur change aussi dans la seconde
+    let mut frames = [first, second];
+    assert!(Document::set_swatch_everywhere(
+        &mut frames,
+        "bleu marque",
+        Some(Color32::RED)
+    ));
+    assert_eq!(frames[1].swatch("bleu marque"), Some(Color32::RED));
+    assert_eq!(frames[1].objects()[0].color(), Some(Color32::RED));
+
+    assert!(Document::set_swatch_everywhere(
+        &mut frames,
+        "bleu marque",
+        None
+    ));
+    assert!(frames.iter().all(|frame| frame.palette().is_empty()));
+    assert!(!Document::set_swatch_everywhere(
+        &mut frames,
+        " ",
+        Some(brand)
+    ));
+}
+
+#[test]
+fn palette_changes_are_announced() {
+    let mut document = Document::new();
+    let events = document.subscribe();
+    let changed =
+        |events: &std::sync::mpsc::Receiver<DocumentEvent>| events.try_iter().collect::<Vec<_>>();
+
+    // Aucun trait ne suit la couleur : seul le changement de palette est annoncé
+    assert!(document.set_swatch("fond", Color32::RED));
+    assert_eq!(
+        changed(&events),
+        [DocumentEvent::PaletteChanged("fond".to_owned())]
+    );
+    assert!(document.undo());
+    assert_eq!(
+        changed(&events),
+        [DocumentEvent::PaletteChanged("fond".to_owned())]
+    );
+    assert!(document.redo());
+    assert!(document.remove_swatch("fond"));
+    assert_eq!(changed(&events).len(), 2);
+    assert!(!document.remove_swatch("fond"));
+    assert!(changed(&events).is_empty());
+}
+
+#[test]
+fn failed_batches_are_not_announced() {
+    let mut document = Document::new();
+    let events = document.subscribe();
+
+    // Le retrait échoue : l'ajout est défait, et ni l'un ni l'autre n'est annoncé
+    let push = Action::Push(stroke(0.0, Color32::RED).into());
+    assert!(!document.apply(Action::Batch(vec![push.clone(), Action::Remove(vec![5])])));
+    assert!(document.is_empty());
+    assert_eq!(events.try_iter().count(), 0);
+
+    assert!(document.apply(Action::Batch(vec![
+        push,
+        Action::Rename(0, Some("trait".to_owned())),
+    ])));
+    let id = document.objects()[0].meta().id;
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        [
+            DocumentEvent::Added(vec![id]),
+            DocumentEvent::Renamed(id, Some("trait".to_owned())),
+        ]
+    );
+}
+
+#[test]
+fn locked_objects_are_saved_and_not_erased() {
+    let mut document = sample();
+    assert!(document.set_locked(2, true));
+    let json = serde_json::to_string(&document).unwrap();
+    let mut read: Document = serde_json::from_str(&json).unwrap();
+    assert!(read.objects()[2].meta().locked);
+    assert_eq!(read.erase_at(pos2(20.0, 20.0), 1.0), 0);
+    assert_eq!(read.len(), 4);
+
+    // Le verrou s'annule comme les autres modifications, et n'est pas écrit une fois retiré
+    assert!(document.undo());
+    assert!(!document.objects()[2].meta().locked);
+    assert!(!serde_json::to_string(&document).unwrap().contains("locked"));
+}
+
+#[test]
+fn text_and_image_round_trip() {
+    let mut document = Document::with_author("lulu");
+    document.apply(Action::Push(DrawObject::text(
+        pos2(0.0, 0.0),
+        "a < b",
+        20.0,
+        Color32::RED,
+    )));
+    let rect = Rect::from_min_max(pos2(100.0, 0.0), pos2(140.0, 30.0));
+    document.apply(Action::Push(DrawObject::image(rect, "photo.png")));
+
+    let json = serde_json::to_string(&document).unwrap();
+    let read: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.objects()[0].kind_name(), "Texte");
+    assert_eq!(read.objects()[1].kind_name(), "Image");
+    for (a, b) in document.objects().iter().zip(read.objects()) {
+        assert_eq!(a.bounds(), b.bounds());
+    }
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+
+    // Touchés dans tout leur cadre, pas seulement sur le contour de remplacement
+    assert_eq!(read.top_object_at(pos2(10.0, 10.0), 0.0), Some(0));
+    assert_eq!(read.top_object_at(pos2(120.0, 15.0), 0.0), Some(1));
+    assert_eq!(read.top_object_at(pos2(80.0, 15.0), 0.0), None);
+
+    let svg = read.export_svg();
+    assert!(svg.contains(">a &lt; b</text>"));
+    assert!(
+        svg.contains("<image x=\"100\" y=\"0\" width=\"40\" height=\"30\" href=\"photo.png\"/>")
+    );
+}
@@ -0,0 +1,193 @@
+// Invariants de l'historique sur des suites d'actions aléatoires, indices invalides compris :
+// une action refusée ne change rien, annuler revient exactement à l'état d'avant et
+// rétablir reproduit l'état d'après, palette comprise. L'index spatial suit toutes ces
+// modifications.
+
+use epaint::{Color32, Pos2, Rect, pos2};
+use proptest::prelude::*;
+use rust_paint::document::{Action, Document, DrawObject, Line};
+
+// Indices tirés un peu au-delà de la taille des dessins générés, pour tester les refus
+const MAX_INDEX: usize = 12;
+
+// Surtout près de l'origine, parfois très loin : l'index doit aussi s'agrandir
+fn point() -> impl Strategy<Value = Pos2> + Clone {
+    let coordinate = prop_oneof![9 => -100.0f32..100.0, 1 => -1e6f32..1e6];
+    (coordinate.clone(), coordinate).prop_map(|(x, y)| pos2(x, y))
+}
+
+fn color() -> impl Strategy<Value = Color32> + Clone {
+    any::<[u8; 3]>().prop_map(|[r, g, b]| Color32::from_rgb(r, g, b))
+}
+
+// Deux noms de couleur seulement, pour que traits et palette se rencontrent
+fn swatch_name() -> impl Strategy<Value = String> + Clone {
+    "[ab]"
+}
+
+fn object() -> impl Strategy<Value = DrawObject> {
+    let point = point();
+    let color = color();
+    prop_oneof![
+        (
+            prop::collection::vec(point.clone(), 1..6),
+            color.clone(),
+            any::<bool>(),
+            prop::option::of(swatch_name()),
+        )
+            .prop_map(
+                |(points, color, erase, swatch)| Line::new(points, color, 3.0, 1.0)
+                    .with_erase(erase)
+                    .with_swatch(swatch)
+                    .into()
+            ),
+        (point.clone(), point, color).prop_map(|(a, b, color)| {
+            DrawObject::rect(Rect::from_two_pos(a, b), color, 2.0, 0.5)
+        }),
+    ]
+}
+
+fn simple_action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        object().prop_map(Action::Push),
+        Just(Action::Pop),
+        prop::collection::vec((0..MAX_INDEX, object()), 0..3).prop_map(Action::Insert),
+        prop::collection::vec(0..MAX_INDEX, 0..4).prop_map(Action::Remove),
+        (0..MAX_INDEX, prop::option::of("[a-z]{1,4}")).prop_map(|(i, n)| Action::Rename(i, n)),
+        (0..MAX_INDEX, any::<bool>()).prop_map(|(i, l)| Action::Lock(i, l)),
+        (swatch_name(), prop::option::of(color())).prop_map(|(n, c)| Action::SetSwatch(n, c)),
+    ]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        3 => simple_action(),
+        1 => prop::collection::vec(simple_action(), 0..4).prop_map(Action::Batch),
+    ]
+}
+
+// État observable du dessin : objets, géométrie et métadonnées comprises, et palette
+fn snapshot(document: &Document) -> serde_json::Value {
+    serde_json::json!({
+        "objects": document.objects(),
+        "palette": document.palette(),
+    })
+}
+
+proptest! {
+    #[test]
+    fn undo_restores_and_redo_reproduces(
+        setup in prop::collection::vec(object(), 0..6),
+        actions in prop::collection::vec(action(), 1..20),
+    ) {
+        let mut document = Document::new();
+        for object in setup {
+            document.apply(Action::Push(object));
+        }
+        for action in actions {
+            let before = snapshot(&document);
+            if !document.apply(action) {
+                prop_assert_eq!(snapshot(&document), before);
+                continue;
+            }
+            let after = snapshot(&document);
+            prop_assert!(document.undo());
+            prop_assert_eq!(snapshot(&document), before);
+            prop_assert!(document.redo());
+            prop_assert_eq!(snapshot(&document), after);
+        }
+    }
+
+    #[test]
+    fn undoing_everything_returns_to_the_start(
+        actions in prop::collection::vec(action(), 1..20),
+    ) {
+        let mut document = Document::new();
+        let mut states = vec![snapshot(&document)];
+        for action in actions {
+            if document.apply(action) {
+                states.push(snapshot(&document));
+            }
+        }
+        for state in states.iter().rev().skip(1) {
+            prop_assert!(document.undo());
+            prop_assert_eq!(&snapshot(&document), state);
+        }
+        prop_assert!(!document.can_undo());
+        for state in states.iter().skip(1) {
+            prop_assert!(document.redo());
+            prop_assert_eq!(&snapshot(&document), state);
+        }
+        prop_assert!(!document.can_redo());
+    }
+
+    #[test]
+    fn ids_stay_unique(actions in prop::collection::vec(action(), 1..30), undos in 0..10usize) {
+        let mut document = Document::new();
+        for action in actions {
+            document.apply(action);
+        }
+        for _ in 0..undos {
+            document.undo();
+        }
+        let mut ids: Vec<u64> = document.objects().iter().map(|object| object.meta().id).collect();
+        prop_assert!(ids.iter().all(|&id| id != 0));
+        ids.sort_unstable();
+        ids.dedup();
+        prop_assert_eq!(ids.len(), document.len());
+    }
+
+    #[test]
+    fn spatial_queries_match_a_full_scan(
+        actions in prop::collection::vec(action(), 1..30),
+        undos in 0..10usize,
+        corners in (point(), point()),
+        pos in point(),
+        tolerance in 0.0f32..20.0,
+    ) {
+        let mut document = Document::new();
+        for action in actions {
+            document.apply(action);
+        }
+        for _ in 0..undos {
+            document.undo();
+        }
+        let area = Rect::from_two_pos(corners.0, corners.1);
+        let scan = |keep: &dyn Fn(&DrawObject) -> bool| -> Vec<usize> {
+            (0..document.len()).filter(|&i| keep(&document.objects()[i])).collect()
+        };
+        prop_assert_eq!(document.objects_in(area), scan(&|o| o.bounds().intersects(area)));
+        prop_assert_eq!(document.objects_at(pos, tolerance), scan(&|o| o.hit(pos, tolerance)));
+        let crossing = |o: &DrawObject| o.lines().iter().any(|line| line.intersects_rect(area));
+        prop_assert_eq!(document.select_rect(area), scan(&crossing));
+
+        // L'index reconstruit à la lecture donne les mêmes réponses
+        let json = serde_json::to_value(&document).unwrap();
+        let read: Document = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(read.objects_in(area), document.objects_in(area));
+    }
+}
+
+#[test]
+fn cleared_objects_stay_in_the_history() {
+    let mut document = Document::new();
+    for x in 0..10 {
+        let x = x as f32;
+        document.add_stroke(Line::new(
+            vec![pos2(x, 0.0), pos2(x, 50.0)],
+            Color32::RED,
+            2.0,
+            1.0,
+        ));
+    }
+    let drawing = document.memory_size();
+    document.clear();
+    assert_eq!(document.memory_size(), 0);
+    assert!(document.undo_memory_size() >= drawing);
+
+    document.undo();
+    assert!(document.redo_memory_size() < document.memory_size());
+    document.clear_history();
+    assert_eq!(document.undo_memory_size() + document.redo_memory_size(), 0);
+    assert!(!document.can_undo() && !document.can_redo());
+}